@@ -0,0 +1,10 @@
+-- 会計年度の設定 (1行のみ)
+CREATE TABLE fiscal_settings
+(
+    id                      INTEGER PRIMARY KEY  DEFAULT 1 CHECK (id = 1),
+    fiscal_year_start_month INTEGER     NOT NULL DEFAULT 4 CHECK (fiscal_year_start_month BETWEEN 1 AND 12),
+    updated_at              TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_by              UUID REFERENCES users (id)
+);
+
+INSERT INTO fiscal_settings (id) VALUES (1);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS ledger_department_mappings;
+DROP TABLE IF EXISTS ledger_account_mappings;
@@ -0,0 +1,22 @@
+-- 会計システムの勘定科目コード → 科目
+-- account_item_idがNULLの行は取り込み対象外 (B/S科目など) として読み飛ばす
+CREATE TABLE ledger_account_mappings
+(
+    id              UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    external_code   TEXT        NOT NULL UNIQUE,
+    account_item_id UUID REFERENCES account_items (id),
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- 会計システムの部門・タグ → ノード (lineage_id) またはサービス
+CREATE TABLE ledger_department_mappings
+(
+    id            UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    external_code TEXT        NOT NULL UNIQUE,
+    lineage_id    UUID,
+    service_id    UUID REFERENCES services (id),
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at    TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK ((lineage_id IS NULL) <> (service_id IS NULL))
+);
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS closing_events;
+DROP TABLE IF EXISTS closed_months;
+DROP TYPE IF EXISTS closing_action;
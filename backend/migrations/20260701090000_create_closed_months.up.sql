@@ -0,0 +1,25 @@
+CREATE TYPE closing_action AS ENUM ('Close', 'Reopen');
+
+-- 確定値を締めた月 (シナリオごと)
+CREATE TABLE closed_months
+(
+    scenario_id  UUID        NOT NULL REFERENCES scenarios (id),
+    target_month DATE        NOT NULL CHECK (EXTRACT(DAY FROM target_month) = 1),
+    closed_at    TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    closed_by    UUID        NOT NULL REFERENCES users (id),
+    PRIMARY KEY (scenario_id, target_month)
+);
+
+-- 締め・締め解除の監査ログ
+CREATE TABLE closing_events
+(
+    id           UUID PRIMARY KEY        DEFAULT gen_random_uuid(),
+    scenario_id  UUID           NOT NULL REFERENCES scenarios (id),
+    target_month DATE           NOT NULL,
+    action       closing_action NOT NULL,
+    reason       TEXT,
+    acted_at     TIMESTAMPTZ    NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    acted_by     UUID           NOT NULL REFERENCES users (id)
+);
+
+CREATE INDEX idx_closing_events_scenario_id ON closing_events (scenario_id);
@@ -0,0 +1,4 @@
+ALTER TABLE scenarios
+    DROP COLUMN IF EXISTS parent_scenario_id,
+    DROP COLUMN IF EXISTS kind;
+DROP TYPE IF EXISTS scenario_kind;
@@ -0,0 +1,8 @@
+CREATE TYPE scenario_kind AS ENUM ('Budget', 'Forecast', 'WhatIf');
+
+-- 分岐シナリオ (作成中のシナリオを変えずに試算するためのコピー)
+ALTER TABLE scenarios
+    ADD COLUMN kind               scenario_kind NOT NULL DEFAULT 'Budget',
+    ADD COLUMN parent_scenario_id UUID REFERENCES scenarios (id);
+
+CREATE INDEX idx_scenarios_parent_scenario_id ON scenarios (parent_scenario_id);
@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS scenario_status_transitions;
+ALTER TABLE scenarios
+    DROP COLUMN IF EXISTS status;
+DROP TYPE IF EXISTS scenario_status;
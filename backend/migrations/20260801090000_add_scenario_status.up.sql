@@ -0,0 +1,18 @@
+CREATE TYPE scenario_status AS ENUM ('Draft', 'Submitted', 'Approved', 'Rejected');
+
+ALTER TABLE scenarios
+    ADD COLUMN status scenario_status NOT NULL DEFAULT 'Draft';
+
+-- シナリオの承認フローの遷移履歴
+CREATE TABLE scenario_status_transitions
+(
+    id          UUID PRIMARY KEY         DEFAULT gen_random_uuid(),
+    scenario_id UUID            NOT NULL REFERENCES scenarios (id),
+    from_status scenario_status NOT NULL,
+    to_status   scenario_status NOT NULL,
+    comment     TEXT,
+    acted_at    TIMESTAMPTZ     NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    acted_by    UUID            NOT NULL REFERENCES users (id)
+);
+
+CREATE INDEX idx_scenario_status_transitions_scenario_id ON scenario_status_transitions (scenario_id);
@@ -0,0 +1,4 @@
+DROP TRIGGER IF EXISTS trg_pl_entries_scenario_period ON pl_entries;
+DROP FUNCTION IF EXISTS check_pl_entry_scenario_period();
+ALTER TABLE pl_entries
+    DROP CONSTRAINT IF EXISTS pl_entries_target_month_first_day;
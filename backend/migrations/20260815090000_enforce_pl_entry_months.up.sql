@@ -0,0 +1,32 @@
+-- 対象月は月初日 (既存の不正データは修復レポートで確認するため、新しい行のみ検証する)
+ALTER TABLE pl_entries
+    ADD CONSTRAINT pl_entries_target_month_first_day
+        CHECK (EXTRACT(DAY FROM target_month) = 1) NOT VALID;
+
+-- 対象月はノードが属するシナリオの期間内
+CREATE FUNCTION check_pl_entry_scenario_period() RETURNS trigger AS
+$$
+DECLARE
+    period_start DATE;
+    period_end   DATE;
+BEGIN
+    SELECT date_trunc('month', s.start_date)::date, s.end_date
+    INTO period_start, period_end
+    FROM plan_nodes n
+             JOIN scenarios s ON s.id = n.scenario_id
+    WHERE n.id = NEW.node_id;
+
+    IF NEW.target_month < period_start OR NEW.target_month > period_end THEN
+        RAISE EXCEPTION 'Month % is outside the scenario period', to_char(NEW.target_month, 'YYYY-MM')
+            USING ERRCODE = 'check_violation';
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_pl_entries_scenario_period
+    BEFORE INSERT OR UPDATE OF target_month, node_id
+    ON pl_entries
+    FOR EACH ROW
+EXECUTE FUNCTION check_pl_entry_scenario_period();
@@ -0,0 +1,3 @@
+ALTER TABLE plan_nodes
+    DROP COLUMN editor_ids,
+    DROP COLUMN owner_id;
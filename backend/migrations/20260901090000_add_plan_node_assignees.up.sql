@@ -0,0 +1,4 @@
+-- ノードの担当者 (子孫ノードにも引き継がれる)
+ALTER TABLE plan_nodes
+    ADD COLUMN owner_id   UUID REFERENCES users (id),
+    ADD COLUMN editor_ids UUID[] NOT NULL DEFAULT '{}';
@@ -0,0 +1 @@
+DROP INDEX uq_plan_nodes_scenario_id_lineage_id;
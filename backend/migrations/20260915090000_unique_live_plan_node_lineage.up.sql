@@ -0,0 +1,4 @@
+-- 削除されていないノードはシナリオ内でlineage_idが重複しない
+CREATE UNIQUE INDEX uq_plan_nodes_scenario_id_lineage_id
+    ON plan_nodes (scenario_id, lineage_id)
+    WHERE deleted_at IS NULL;
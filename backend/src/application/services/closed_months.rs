@@ -0,0 +1,101 @@
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use crate::domain::closed_months::{
+    self, ClosedMonth, ClosedMonthRepository, ClosingAction, ClosingEvent,
+};
+use crate::domain::scenarios::{Scenario, ScenarioRepository};
+
+pub struct ClosedMonthService<C, S> {
+    closed_month_repo: C,
+    scenario_repo: S,
+}
+
+impl<C, S> ClosedMonthService<C, S>
+where
+    C: ClosedMonthRepository,
+    S: ScenarioRepository,
+{
+    pub fn new(closed_month_repo: C, scenario_repo: S) -> Self {
+        Self {
+            closed_month_repo,
+            scenario_repo,
+        }
+    }
+
+    async fn find_scenario(&self, scenario_id: Uuid) -> anyhow::Result<Scenario> {
+        self.scenario_repo
+            .find_by_id(scenario_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Scenario not found"))
+    }
+
+    pub async fn list(&self, scenario_id: Uuid) -> anyhow::Result<Vec<ClosedMonth>> {
+        self.find_scenario(scenario_id).await?;
+
+        self.closed_month_repo
+            .find_by_scenario_id(scenario_id)
+            .await
+    }
+
+    /// 月を締め、以降その月の確定値を編集できないようにする
+    pub async fn close(
+        &self,
+        scenario_id: Uuid,
+        target_month: NaiveDate,
+        user_id: Uuid,
+    ) -> anyhow::Result<ClosedMonth> {
+        let scenario = self.find_scenario(scenario_id).await?;
+        if scenario.is_locked {
+            return Err(anyhow::anyhow!(
+                "Read-Only: Locked scenarios cannot be edited"
+            ));
+        }
+        if !scenario.status.is_editable() {
+            return Err(anyhow::anyhow!(
+                "Read-Only: Submitted or approved scenarios cannot be edited"
+            ));
+        }
+        let month = ClosedMonth::new(&scenario, target_month, user_id)?;
+
+        let event = ClosingEvent::new(
+            scenario_id,
+            target_month,
+            ClosingAction::Close,
+            None,
+            user_id,
+        );
+        self.closed_month_repo.close(&month, &event).await?;
+
+        Ok(month)
+    }
+
+    /// 締めを解除する。理由は監査ログに残す
+    pub async fn reopen(
+        &self,
+        scenario_id: Uuid,
+        target_month: NaiveDate,
+        reason: String,
+        user_id: Uuid,
+    ) -> anyhow::Result<ClosingEvent> {
+        let scenario = self.find_scenario(scenario_id).await?;
+        closed_months::validate_target_month(&scenario, target_month)?;
+
+        let event = ClosingEvent::new(
+            scenario_id,
+            target_month,
+            ClosingAction::Reopen,
+            Some(reason),
+            user_id,
+        );
+        self.closed_month_repo.reopen(&event).await?;
+
+        Ok(event)
+    }
+
+    pub async fn list_events(&self, scenario_id: Uuid) -> anyhow::Result<Vec<ClosingEvent>> {
+        self.find_scenario(scenario_id).await?;
+
+        self.closed_month_repo.find_events(scenario_id).await
+    }
+}
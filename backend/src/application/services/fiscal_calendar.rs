@@ -0,0 +1,27 @@
+use uuid::Uuid;
+
+use crate::domain::fiscal_calendar::{FiscalCalendar, FiscalCalendarRepository};
+
+pub struct FiscalCalendarService<R: FiscalCalendarRepository> {
+    repository: R,
+}
+
+impl<R: FiscalCalendarRepository> FiscalCalendarService<R> {
+    pub fn new(repository: R) -> Self {
+        Self { repository }
+    }
+
+    pub async fn get(&self) -> anyhow::Result<FiscalCalendar> {
+        self.repository.find().await
+    }
+
+    pub async fn update(
+        &self,
+        fiscal_year_start_month: i32,
+        user_id: Uuid,
+    ) -> anyhow::Result<FiscalCalendar> {
+        let calendar = FiscalCalendar::new(fiscal_year_start_month, user_id)?;
+
+        self.repository.update(&calendar).await
+    }
+}
@@ -0,0 +1,52 @@
+use uuid::Uuid;
+
+use crate::domain::ledger_mappings::{
+    LedgerAccountMapping, LedgerDepartmentMapping, LedgerMappingRepository,
+};
+
+pub struct LedgerMappingService<R: LedgerMappingRepository> {
+    repository: R,
+}
+
+impl<R: LedgerMappingRepository> LedgerMappingService<R> {
+    pub fn new(repository: R) -> Self {
+        Self { repository }
+    }
+
+    pub async fn list_accounts(&self) -> anyhow::Result<Vec<LedgerAccountMapping>> {
+        self.repository.find_account_mappings().await
+    }
+
+    pub async fn save_account(
+        &self,
+        external_code: String,
+        account_item_id: Option<Uuid>,
+    ) -> anyhow::Result<LedgerAccountMapping> {
+        let mapping = LedgerAccountMapping::new(external_code, account_item_id)?;
+
+        self.repository.upsert_account_mapping(&mapping).await
+    }
+
+    pub async fn delete_account(&self, id: Uuid) -> anyhow::Result<()> {
+        self.repository.delete_account_mapping(id).await
+    }
+
+    pub async fn list_departments(&self) -> anyhow::Result<Vec<LedgerDepartmentMapping>> {
+        self.repository.find_department_mappings().await
+    }
+
+    pub async fn save_department(
+        &self,
+        external_code: String,
+        lineage_id: Option<Uuid>,
+        service_id: Option<Uuid>,
+    ) -> anyhow::Result<LedgerDepartmentMapping> {
+        let mapping = LedgerDepartmentMapping::new(external_code, lineage_id, service_id)?;
+
+        self.repository.upsert_department_mapping(&mapping).await
+    }
+
+    pub async fn delete_department(&self, id: Uuid) -> anyhow::Result<()> {
+        self.repository.delete_department_mapping(id).await
+    }
+}
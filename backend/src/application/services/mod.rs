@@ -1,6 +1,13 @@
 pub mod account_items;
 pub mod auth;
+pub mod closed_months;
+pub mod fiscal_calendar;
+pub mod ledger_mappings;
 pub mod pl_entries;
+pub mod pl_reports;
 pub mod plan_nodes;
+pub mod scenario_merges;
+pub mod scenario_snapshots;
 pub mod scenarios;
+#[allow(clippy::module_inception)]
 pub mod services;
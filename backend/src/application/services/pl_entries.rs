@@ -1,16 +1,27 @@
-use chrono::{NaiveDate, Utc};
+use chrono::{Datelike, NaiveDate, Utc};
 use rust_decimal::Decimal;
 use sqlx::{PgConnection, PgPool};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
 use uuid::Uuid;
 
 use crate::domain::scenarios::ScenarioRepository;
 use crate::{
     domain::{
+        account_items::{AccountItem, AccountItemRepository, AccountType},
+        closed_months::{ClosedMonthRepository, MonthClosedError},
         history::{ChangeType, PlEntryHistory, PlEntryHistoryRepository},
-        pl_entries::{EntryCategory, PlEntry, PlEntryRepository},
-        plan_nodes::PlanNodeRepository,
+        ledger_mappings::{
+            self, LedgerAccountMapping, LedgerDepartmentMapping, LedgerMappingRepository,
+        },
+        pl_entries::{EntryCategory, InvalidMonthEntry, PlEntry, PlEntryRepository},
+        plan_nodes::{self, PlanNode, PlanNodeRepository},
+        user::UserRole,
+    },
+    presentation::dtos::{
+        LedgerCsvRow, LedgerImportCell, LedgerImportReport, PlEntryCsvRow, PlEntryImportError,
+        PlEntryImportReport, SavePlEntryRequest,
     },
-    presentation::dtos::SavePlEntryRequest,
 };
 
 pub struct PlEntryService<
@@ -18,12 +29,18 @@ pub struct PlEntryService<
     N: PlanNodeRepository,
     H: PlEntryHistoryRepository,
     S: ScenarioRepository,
+    A: AccountItemRepository,
+    M: LedgerMappingRepository,
+    C: ClosedMonthRepository,
 > {
     pool: PgPool,
     entry_repo: R,
     node_repo: N,
     history_repo: H,
     scenario_repo: S,
+    account_item_repo: A,
+    ledger_mapping_repo: M,
+    closed_month_repo: C,
 }
 
 impl<
@@ -31,14 +48,21 @@ impl<
     N: PlanNodeRepository,
     H: PlEntryHistoryRepository,
     S: ScenarioRepository,
-> PlEntryService<R, N, H, S>
+    A: AccountItemRepository,
+    M: LedgerMappingRepository,
+    C: ClosedMonthRepository,
+> PlEntryService<R, N, H, S, A, M, C>
 {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         pool: PgPool,
         entry_repo: R,
         node_repo: N,
         history_repo: H,
         scenario_repo: S,
+        account_item_repo: A,
+        ledger_mapping_repo: M,
+        closed_month_repo: C,
     ) -> Self {
         Self {
             pool,
@@ -46,11 +70,19 @@ impl<
             node_repo,
             history_repo,
             scenario_repo,
+            account_item_repo,
+            ledger_mapping_repo,
+            closed_month_repo,
         }
     }
 
     // 書き込み権限とノードタイプのチェックを行うヘルパーメソッド
-    async fn ensure_writable(&self, node_id: Uuid) -> anyhow::Result<()> {
+    async fn ensure_writable(
+        &self,
+        node_id: Uuid,
+        user_id: Uuid,
+        role: &UserRole,
+    ) -> anyhow::Result<()> {
         // 存在確認
         let node = self
             .node_repo
@@ -72,15 +104,56 @@ impl<
             .await?
             .ok_or_else(|| anyhow::anyhow!("Scenario not found"))?;
 
-        if !scenario.is_current {
+        if scenario.is_locked {
+            return Err(anyhow::anyhow!(
+                "Read-Only: Locked scenarios cannot be edited"
+            ));
+        }
+
+        if !scenario.status.is_editable() {
+            return Err(anyhow::anyhow!(
+                "Read-Only: Submitted or approved scenarios cannot be edited"
+            ));
+        }
+
+        if !scenario.is_editable_by(user_id) {
             return Err(anyhow::anyhow!(
-                "Read-Only: Past scenarios cannot be edited"
+                "Read-Only: Only the current scenario or your own branch can be edited"
+            ));
+        }
+
+        // Memberは担当するノード (祖先が担当する場合を含む) のみ入力できる
+        let ancestors = self.node_repo.find_with_ancestors(node_id).await?;
+        if !plan_nodes::can_edit_in(&scenario, &ancestors, Some(node_id), user_id, role) {
+            return Err(anyhow::anyhow!(
+                "Read-Only: You are not assigned to this node"
             ));
         }
 
         Ok(())
     }
 
+    // ensure_writableのエラーメッセージをノードごとにキャッシュしながら返す
+    async fn writable_error(
+        &self,
+        cache: &mut HashMap<Uuid, Option<String>>,
+        node_id: Uuid,
+        user_id: Uuid,
+        role: &UserRole,
+    ) -> Option<String> {
+        if let Some(cached) = cache.get(&node_id) {
+            return cached.clone();
+        }
+
+        let message = self
+            .ensure_writable(node_id, user_id, role)
+            .await
+            .err()
+            .map(|e| e.to_string());
+        cache.insert(node_id, message.clone());
+        message
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub async fn save_entry(
         &self,
@@ -91,9 +164,10 @@ impl<
         amount: Decimal,
         description: Option<String>,
         user_id: Uuid,
+        role: &UserRole,
     ) -> anyhow::Result<PlEntry> {
         // チェックを実施
-        self.ensure_writable(node_id).await?;
+        self.ensure_writable(node_id, user_id, role).await?;
 
         // トランザクション開始
         let mut tx = self.pool.begin().await?;
@@ -109,6 +183,7 @@ impl<
                 amount,
                 description,
                 user_id,
+                "Bulk/API",
             )
             .await?;
 
@@ -122,13 +197,14 @@ impl<
         &self,
         requests: Vec<SavePlEntryRequest>,
         user_id: Uuid,
+        role: &UserRole,
     ) -> anyhow::Result<()> {
         // トランザクション開始
         let mut tx = self.pool.begin().await?;
 
         for req in requests {
             // ノードの種類チェック
-            self.ensure_writable(req.node_id).await?;
+            self.ensure_writable(req.node_id, user_id, role).await?;
 
             // ロジックの実行
             self.save_entry_logic(
@@ -140,6 +216,7 @@ impl<
                 req.amount,
                 req.description,
                 user_id,
+                "Bulk/API",
             )
             .await?;
         }
@@ -149,6 +226,7 @@ impl<
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn save_entry_logic(
         &self,
         tx: &mut PgConnection,
@@ -159,7 +237,26 @@ impl<
         amount: Decimal,
         description: Option<String>,
         user_id: Uuid,
+        operation_source: &str,
     ) -> anyhow::Result<PlEntry> {
+        // 対象月は月初日かつシナリオ期間内
+        let scenario = self
+            .scenario_repo
+            .find_by_node_id(tx, node_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Node not found"))?;
+        scenario.validate_target_month(target_month)?;
+
+        // 締めた月の確定値は編集できない
+        if entry_category == EntryCategory::Result
+            && self
+                .closed_month_repo
+                .is_closed_for_node(tx, node_id, target_month)
+                .await?
+        {
+            return Err(MonthClosedError { target_month }.into());
+        }
+
         let existing_entries = self
             .entry_repo
             .find_by_cell(tx, node_id, account_item_id, target_month, &entry_category)
@@ -179,7 +276,7 @@ impl<
                 Some(entry.amount),
                 amount,
                 user_id,
-                Some("Bulk/API".to_string()),
+                Some(operation_source.to_string()),
             );
 
             // update処理
@@ -214,7 +311,7 @@ impl<
                 None,
                 created.amount,
                 user_id,
-                Some("Bulk/API".to_string()),
+                Some(operation_source.to_string()),
             );
 
             self.history_repo.create(tx, &history).await?;
@@ -237,4 +334,520 @@ impl<
     pub async fn list_by_scenario(&self, scenario_id: Uuid) -> anyhow::Result<Vec<PlEntry>> {
         self.entry_repo.find_by_scenario_id(scenario_id).await
     }
+
+    /// 対象月が月初日でない、またはシナリオ期間外の既存Entryを一覧にする (修復用)
+    pub async fn list_invalid_months(&self) -> anyhow::Result<Vec<InvalidMonthEntry>> {
+        self.entry_repo.find_invalid_months().await
+    }
+
+    /// CSVからEntryを取り込む
+    /// 1行でもエラーがある場合、またはdry_runの場合は何も書き込まずに行ごとの検証結果を返す
+    pub async fn import_csv(
+        &self,
+        scenario_id: Uuid,
+        csv_text: &str,
+        dry_run: bool,
+        user_id: Uuid,
+        role: &UserRole,
+    ) -> anyhow::Result<PlEntryImportReport> {
+        let scenario = self
+            .scenario_repo
+            .find_by_id(scenario_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Scenario not found"))?;
+
+        let nodes = self.node_repo.find_by_scenario_id(scenario_id).await?;
+        let closed_months: HashSet<NaiveDate> = self
+            .closed_month_repo
+            .find_by_scenario_id(scenario_id)
+            .await?
+            .into_iter()
+            .map(|m| m.target_month)
+            .collect();
+        let account_items = self.account_item_repo.find_all().await?;
+        let lookup = CsvLookup::new(&nodes, &account_items);
+
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(csv_text.as_bytes());
+
+        let mut requests: Vec<SavePlEntryRequest> = Vec::new();
+        let mut errors: Vec<PlEntryImportError> = Vec::new();
+        let mut total_rows = 0;
+
+        // ノードごとの書き込み可否チェックの結果をキャッシュする
+        let mut writable: HashMap<Uuid, Option<String>> = HashMap::new();
+
+        for (i, record) in reader.deserialize::<PlEntryCsvRow>().enumerate() {
+            // ヘッダーを1行目として数える
+            let row = i + 2;
+            total_rows += 1;
+
+            let parsed = record
+                .map_err(|e| anyhow::anyhow!("Invalid row: {}", e))
+                .and_then(|r| lookup.resolve(r));
+
+            let req = match parsed {
+                Ok(req) => req,
+                Err(e) => {
+                    errors.push(PlEntryImportError {
+                        row,
+                        message: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+
+            if let Some(message) = self
+                .writable_error(&mut writable, req.node_id, user_id, role)
+                .await
+            {
+                errors.push(PlEntryImportError { row, message });
+                continue;
+            }
+
+            if let Err(e) = scenario.validate_target_month(req.target_month) {
+                errors.push(PlEntryImportError {
+                    row,
+                    message: e.to_string(),
+                });
+                continue;
+            }
+
+            if req.entry_category == EntryCategory::Result
+                && closed_months.contains(&req.target_month)
+            {
+                let error = MonthClosedError {
+                    target_month: req.target_month,
+                };
+                errors.push(PlEntryImportError {
+                    row,
+                    message: error.to_string(),
+                });
+                continue;
+            }
+
+            requests.push(req);
+        }
+
+        let valid_rows = requests.len();
+        if dry_run || !errors.is_empty() {
+            return Ok(PlEntryImportReport {
+                dry_run,
+                total_rows,
+                valid_rows,
+                imported_rows: 0,
+                errors,
+            });
+        }
+
+        // トランザクション開始
+        let mut tx = self.pool.begin().await?;
+
+        for req in requests {
+            self.save_entry_logic(
+                &mut tx,
+                req.node_id,
+                req.account_item_id,
+                req.target_month,
+                req.entry_category,
+                req.amount,
+                req.description,
+                user_id,
+                "CSV Import",
+            )
+            .await?;
+        }
+
+        // コミット
+        tx.commit().await?;
+
+        Ok(PlEntryImportReport {
+            dry_run,
+            total_rows,
+            valid_rows,
+            imported_rows: valid_rows,
+            errors,
+        })
+    }
+
+    /// 会計システムの仕訳帳・試算表CSVから確定値を取り込む
+    /// 勘定科目コード・部門は保存済みの対応表で科目・ノードに変換し、ノード × 科目 × 月で合算する
+    /// 合算した金額でEntryを上書きするため、同じ月のファイルを再度取り込んでも結果は変わらない
+    pub async fn import_ledger(
+        &self,
+        scenario_id: Uuid,
+        csv_text: &str,
+        dry_run: bool,
+        user_id: Uuid,
+        role: &UserRole,
+    ) -> anyhow::Result<LedgerImportReport> {
+        let scenario = self
+            .scenario_repo
+            .find_by_id(scenario_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Scenario not found"))?;
+
+        let nodes = self.node_repo.find_by_scenario_id(scenario_id).await?;
+        let closed_months: HashSet<NaiveDate> = self
+            .closed_month_repo
+            .find_by_scenario_id(scenario_id)
+            .await?
+            .into_iter()
+            .map(|m| m.target_month)
+            .collect();
+        let account_items = self.account_item_repo.find_all().await?;
+        let lookup = LedgerLookup::new(
+            &nodes,
+            &account_items,
+            self.ledger_mapping_repo.find_account_mappings().await?,
+            self.ledger_mapping_repo.find_department_mappings().await?,
+        );
+
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(csv_text.as_bytes());
+
+        let mut cells: HashMap<(Uuid, Uuid, NaiveDate), Decimal> = HashMap::new();
+        let mut errors: Vec<PlEntryImportError> = Vec::new();
+        let mut total_rows = 0;
+        let mut valid_rows = 0;
+        let mut skipped_rows = 0;
+        let mut writable: HashMap<Uuid, Option<String>> = HashMap::new();
+
+        for (i, record) in reader.deserialize::<LedgerCsvRow>().enumerate() {
+            // ヘッダーを1行目として数える
+            let row = i + 2;
+            total_rows += 1;
+
+            let parsed = record
+                .map_err(|e| anyhow::anyhow!("Invalid row: {}", e))
+                .and_then(|r| lookup.resolve(r));
+
+            let (node_id, account_item_id, target_month, amount) = match parsed {
+                Ok(Some(cell)) => cell,
+                Ok(None) => {
+                    skipped_rows += 1;
+                    continue;
+                }
+                Err(e) => {
+                    errors.push(PlEntryImportError {
+                        row,
+                        message: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+
+            if let Err(e) = scenario.validate_target_month(target_month) {
+                errors.push(PlEntryImportError {
+                    row,
+                    message: e.to_string(),
+                });
+                continue;
+            }
+
+            if let Some(message) = self
+                .writable_error(&mut writable, node_id, user_id, role)
+                .await
+            {
+                errors.push(PlEntryImportError { row, message });
+                continue;
+            }
+
+            if closed_months.contains(&target_month) {
+                errors.push(PlEntryImportError {
+                    row,
+                    message: MonthClosedError { target_month }.to_string(),
+                });
+                continue;
+            }
+
+            valid_rows += 1;
+            *cells
+                .entry((node_id, account_item_id, target_month))
+                .or_default() += amount;
+        }
+
+        let mut entries: Vec<LedgerImportCell> = cells
+            .into_iter()
+            .map(
+                |((node_id, account_item_id, target_month), amount)| LedgerImportCell {
+                    node_id,
+                    account_item_id,
+                    target_month,
+                    amount,
+                },
+            )
+            .collect();
+        entries.sort_by_key(|c| (c.target_month, c.node_id, c.account_item_id));
+
+        if dry_run || !errors.is_empty() {
+            return Ok(LedgerImportReport {
+                dry_run,
+                total_rows,
+                valid_rows,
+                skipped_rows,
+                imported_entries: 0,
+                entries,
+                errors,
+            });
+        }
+
+        // トランザクション開始
+        let mut tx = self.pool.begin().await?;
+
+        for cell in &entries {
+            self.save_entry_logic(
+                &mut tx,
+                cell.node_id,
+                cell.account_item_id,
+                cell.target_month,
+                EntryCategory::Result,
+                cell.amount,
+                None,
+                user_id,
+                "Ledger Import",
+            )
+            .await?;
+        }
+
+        // コミット
+        tx.commit().await?;
+
+        Ok(LedgerImportReport {
+            dry_run,
+            total_rows,
+            valid_rows,
+            skipped_rows,
+            imported_entries: entries.len(),
+            entries,
+            errors,
+        })
+    }
+}
+
+// CSVの各列をノード・科目に解決するための索引
+struct CsvLookup {
+    by_lineage: HashMap<Uuid, Uuid>,
+    by_path: HashMap<String, Vec<Uuid>>,
+    by_code: HashMap<String, Uuid>,
+}
+
+impl CsvLookup {
+    fn new(nodes: &[PlanNode], account_items: &[AccountItem]) -> Self {
+        let mut by_path: HashMap<String, Vec<Uuid>> = HashMap::new();
+        for (node, path) in plan_nodes::depth_first(nodes) {
+            by_path.entry(path.join("/")).or_default().push(node.id);
+        }
+
+        Self {
+            by_lineage: nodes.iter().map(|n| (n.lineage_id, n.id)).collect(),
+            by_path,
+            by_code: account_items
+                .iter()
+                .map(|item| (item.code.clone(), item.id))
+                .collect(),
+        }
+    }
+
+    fn resolve(&self, row: PlEntryCsvRow) -> anyhow::Result<SavePlEntryRequest> {
+        let node_id = match (&row.lineage_id, &row.node_path) {
+            (Some(lineage), _) => {
+                let lineage_id = Uuid::parse_str(lineage)
+                    .map_err(|_| anyhow::anyhow!("Invalid lineage_id: {}", lineage))?;
+                *self
+                    .by_lineage
+                    .get(&lineage_id)
+                    .ok_or_else(|| anyhow::anyhow!("Node not found for lineage_id: {}", lineage))?
+            }
+            (None, Some(path)) => match self.by_path.get(path).map(|ids| ids.as_slice()) {
+                Some([id]) => *id,
+                Some(_) => return Err(anyhow::anyhow!("Node path is ambiguous: {}", path)),
+                None => return Err(anyhow::anyhow!("Node not found for path: {}", path)),
+            },
+            (None, None) => {
+                return Err(anyhow::anyhow!(
+                    "Either lineage_id or node_path is required"
+                ));
+            }
+        };
+
+        let account_item_id = *self.by_code.get(&row.account_item_code).ok_or_else(|| {
+            anyhow::anyhow!("Account item not found for code: {}", row.account_item_code)
+        })?;
+
+        Ok(SavePlEntryRequest {
+            node_id,
+            account_item_id,
+            target_month: parse_month(&row.target_month)?,
+            entry_category: parse_category(&row.entry_category)?,
+            amount: parse_amount(&row.amount)?,
+            description: row.description,
+        })
+    }
+}
+
+// 仕訳帳CSVの勘定科目コード・部門をノード・科目に解決するための索引
+struct LedgerLookup {
+    // 値がNoneのコードは取り込み対象外
+    accounts: HashMap<String, Option<Uuid>>,
+    account_types: HashMap<Uuid, AccountType>,
+    // 部門コードごとの解決結果 (解決できない場合はエラーメッセージ)
+    departments: HashMap<String, Result<Uuid, String>>,
+}
+
+impl LedgerLookup {
+    fn new(
+        nodes: &[PlanNode],
+        account_items: &[AccountItem],
+        account_mappings: Vec<LedgerAccountMapping>,
+        department_mappings: Vec<LedgerDepartmentMapping>,
+    ) -> Self {
+        let departments = department_mappings
+            .into_iter()
+            .map(|m| {
+                let resolved = match (m.lineage_id, m.service_id) {
+                    (Some(lineage_id), _) => nodes
+                        .iter()
+                        .find(|n| n.lineage_id == lineage_id)
+                        .map(|n| n.id)
+                        .ok_or_else(|| {
+                            format!("Node not found in this scenario for department: {}", m.external_code)
+                        }),
+                    // サービスへの対応は、そのサービスに紐づく実体ノードが1つだけの場合に限る
+                    (None, Some(service_id)) => {
+                        let candidates: Vec<Uuid> = nodes
+                            .iter()
+                            .filter(|n| n.service_id == Some(service_id) && n.node_type.is_entity())
+                            .map(|n| n.id)
+                            .collect();
+                        match candidates.as_slice() {
+                            [id] => Ok(*id),
+                            [] => Err(format!(
+                                "No entity node is linked to the service for department: {}",
+                                m.external_code
+                            )),
+                            _ => Err(format!(
+                                "Multiple entity nodes are linked to the service for department: {}",
+                                m.external_code
+                            )),
+                        }
+                    }
+                    (None, None) => Err(format!("Invalid mapping for department: {}", m.external_code)),
+                };
+                (m.external_code, resolved)
+            })
+            .collect();
+
+        Self {
+            accounts: account_mappings
+                .into_iter()
+                .map(|m| (m.external_code, m.account_item_id))
+                .collect(),
+            account_types: account_items
+                .iter()
+                .map(|item| (item.id, item.account_type.clone()))
+                .collect(),
+            departments,
+        }
+    }
+
+    // 取り込み対象外の勘定科目の行はNoneを返す
+    fn resolve(
+        &self,
+        row: LedgerCsvRow,
+    ) -> anyhow::Result<Option<(Uuid, Uuid, NaiveDate, Decimal)>> {
+        let account_item_id = match self.accounts.get(&row.account_code) {
+            Some(Some(id)) => *id,
+            Some(None) => return Ok(None),
+            None => {
+                return Err(anyhow::anyhow!(
+                    "Unmapped account code: {}",
+                    row.account_code
+                ));
+            }
+        };
+
+        let department = row
+            .department
+            .ok_or_else(|| anyhow::anyhow!("Department is required"))?;
+        let node_id = match self.departments.get(&department) {
+            Some(Ok(id)) => *id,
+            Some(Err(message)) => return Err(anyhow::anyhow!("{}", message)),
+            None => return Err(anyhow::anyhow!("Unmapped department: {}", department)),
+        };
+
+        let target_month = parse_ledger_month(&row.date)?;
+
+        let amount = match row.amount {
+            Some(amount) => parse_amount(&amount)?,
+            None => {
+                if row.debit.is_none() && row.credit.is_none() {
+                    return Err(anyhow::anyhow!("Either amount or debit/credit is required"));
+                }
+                let debit = row
+                    .debit
+                    .as_deref()
+                    .map(parse_amount)
+                    .transpose()?
+                    .unwrap_or_default();
+                let credit = row
+                    .credit
+                    .as_deref()
+                    .map(parse_amount)
+                    .transpose()?
+                    .unwrap_or_default();
+
+                ledger_mappings::signed_amount(
+                    self.account_types.get(&account_item_id),
+                    debit,
+                    credit,
+                )
+            }
+        };
+
+        Ok(Some((node_id, account_item_id, target_month, amount)))
+    }
+}
+
+fn parse_amount(value: &str) -> anyhow::Result<Decimal> {
+    Decimal::from_str(&value.replace(',', ""))
+        .map_err(|_| anyhow::anyhow!("Invalid amount: {}", value))
+}
+
+// 仕訳日 (YYYY-MM-DD, YYYY/MM/DD) または年月 (YYYY-MM, YYYY/MM) を月初日に寄せる
+fn parse_ledger_month(value: &str) -> anyhow::Result<NaiveDate> {
+    let normalized = value.replace('/', "-");
+    let date = NaiveDate::parse_from_str(&normalized, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(&format!("{}-01", normalized), "%Y-%m-%d"))
+        .map_err(|_| anyhow::anyhow!("Invalid date: {}", value))?;
+
+    Ok(date.with_day(1).expect("first day of month"))
+}
+
+// "YYYY-MM" または "YYYY-MM-01" を月初日として解釈する
+fn parse_month(value: &str) -> anyhow::Result<NaiveDate> {
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(&format!("{}-01", value), "%Y-%m-%d"))
+        .map_err(|_| anyhow::anyhow!("Invalid target_month: {}", value))?;
+
+    if date.day() != 1 {
+        return Err(anyhow::anyhow!(
+            "target_month must be the first day of a month: {}",
+            value
+        ));
+    }
+
+    Ok(date)
+}
+
+fn parse_category(value: &str) -> anyhow::Result<EntryCategory> {
+    if value.eq_ignore_ascii_case("Plan") {
+        Ok(EntryCategory::Plan)
+    } else if value.eq_ignore_ascii_case("Result") {
+        Ok(EntryCategory::Result)
+    } else {
+        Err(anyhow::anyhow!("Invalid entry_category: {}", value))
+    }
 }
@@ -0,0 +1,338 @@
+use chrono::{Datelike, NaiveDate};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::domain::account_items::{AccountItemRepository, AccountType};
+use crate::domain::fiscal_calendar::{FiscalCalendarRepository, PeriodGranularity};
+use crate::domain::pl_entries::{EntryCategory, PlEntry, PlEntryRepository};
+use crate::domain::pl_reports::{
+    self, Forecast, NodeComparison, NodeRollup, NodeVariance, PlGrid, PlStatement, ReportColumn,
+    ScenarioExport, ServicePl,
+};
+use crate::domain::plan_nodes::PlanNodeRepository;
+use crate::domain::scenarios::{Scenario, ScenarioRepository};
+use crate::domain::services::ServiceRepository;
+
+pub struct PlReportService<S, N, E, A, V, F> {
+    scenario_repo: S,
+    node_repo: N,
+    entry_repo: E,
+    account_item_repo: A,
+    service_repo: V,
+    fiscal_calendar_repo: F,
+}
+
+impl<S, N, E, A, V, F> PlReportService<S, N, E, A, V, F>
+where
+    S: ScenarioRepository,
+    N: PlanNodeRepository,
+    E: PlEntryRepository,
+    A: AccountItemRepository,
+    V: ServiceRepository,
+    F: FiscalCalendarRepository,
+{
+    pub fn new(
+        scenario_repo: S,
+        node_repo: N,
+        entry_repo: E,
+        account_item_repo: A,
+        service_repo: V,
+        fiscal_calendar_repo: F,
+    ) -> Self {
+        Self {
+            scenario_repo,
+            node_repo,
+            entry_repo,
+            account_item_repo,
+            service_repo,
+            fiscal_calendar_repo,
+        }
+    }
+
+    async fn find_scenario(&self, scenario_id: Uuid) -> anyhow::Result<Scenario> {
+        self.scenario_repo
+            .find_by_id(scenario_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Scenario not found"))
+    }
+
+    // 科目IDから科目タイプを引くためのマップ
+    async fn account_types(&self) -> anyhow::Result<HashMap<Uuid, AccountType>> {
+        let items = self.account_item_repo.find_all().await?;
+        Ok(items
+            .into_iter()
+            .map(|item| (item.id, item.account_type))
+            .collect())
+    }
+
+    // 期間単位が指定された場合、各Entryの対象月を会計期間の最初の月に寄せる
+    // 戻り値は期間の最初の月から期間ラベルを引くためのマップ
+    async fn bucket_entries(
+        &self,
+        scenario: &Scenario,
+        entries: &mut [PlEntry],
+        period: Option<PeriodGranularity>,
+    ) -> anyhow::Result<HashMap<NaiveDate, String>> {
+        let mut labels = HashMap::new();
+        let Some(granularity) = period else {
+            return Ok(labels);
+        };
+
+        let calendar = self.fiscal_calendar_repo.find().await?;
+        // 四半期の区切りに沿っていないシナリオは、四半期の集計が期間の途中で切れてしまう
+        if granularity == PeriodGranularity::Quarter {
+            calendar.validate_scenario_period(scenario.start_date, scenario.end_date)?;
+        }
+        for entry in entries.iter_mut() {
+            let fiscal_period = calendar.period_of(entry.target_month, granularity);
+            entry.target_month = fiscal_period.start_month;
+            labels.insert(fiscal_period.start_month, fiscal_period.label);
+        }
+
+        Ok(labels)
+    }
+
+    pub async fn rollup(
+        &self,
+        scenario_id: Uuid,
+        period: Option<PeriodGranularity>,
+    ) -> anyhow::Result<Vec<NodeRollup>> {
+        let scenario = self.find_scenario(scenario_id).await?;
+
+        let nodes = self.node_repo.find_by_scenario_id(scenario_id).await?;
+        let mut entries = self.entry_repo.find_by_scenario_id(scenario_id).await?;
+        let labels = self.bucket_entries(&scenario, &mut entries, period).await?;
+
+        let mut rollups = pl_reports::rollup(&nodes, &entries);
+        for cell in rollups.iter_mut().flat_map(|r| r.cells.iter_mut()) {
+            cell.period = labels.get(&cell.target_month).cloned();
+        }
+
+        Ok(rollups)
+    }
+
+    /// シナリオ全体、またはノード配下・サービス単位に絞り込んだP/Lを作成する
+    pub async fn pl_statement(
+        &self,
+        scenario_id: Uuid,
+        category: EntryCategory,
+        node_id: Option<Uuid>,
+        service_id: Option<Uuid>,
+        period: Option<PeriodGranularity>,
+    ) -> anyhow::Result<PlStatement> {
+        let scenario = self.find_scenario(scenario_id).await?;
+
+        let nodes = self.node_repo.find_by_scenario_id(scenario_id).await?;
+        if let Some(id) = node_id
+            && !nodes.iter().any(|n| n.id == id)
+        {
+            return Err(anyhow::anyhow!("Node not found"));
+        }
+
+        let parents = pl_reports::parent_map(&nodes);
+        let services: HashMap<Uuid, Option<Uuid>> =
+            nodes.iter().map(|n| (n.id, n.service_id)).collect();
+
+        let mut entries = self.entry_repo.find_by_scenario_id(scenario_id).await?;
+        let labels = self.bucket_entries(&scenario, &mut entries, period).await?;
+        let account_types = self.account_types().await?;
+
+        let filtered = entries.iter().filter(|e| {
+            e.entry_category == category
+                && node_id
+                    .is_none_or(|id| pl_reports::ancestors_of(e.node_id, &parents).contains(&id))
+                && service_id
+                    .is_none_or(|id| services.get(&e.node_id).copied().flatten() == Some(id))
+        });
+
+        let mut statement = pl_reports::pl_statement(filtered, &account_types);
+        for month in statement.months.iter_mut() {
+            month.period = labels.get(&month.target_month).cloned();
+        }
+
+        Ok(statement)
+    }
+
+    /// 計画値と確定値の差異をセル単位・ノード単位で計算する
+    /// 月の範囲とサービスで集計対象を、node_idで返却するサブツリーを絞り込む
+    pub async fn variance(
+        &self,
+        scenario_id: Uuid,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+        service_id: Option<Uuid>,
+        node_id: Option<Uuid>,
+        period: Option<PeriodGranularity>,
+    ) -> anyhow::Result<Vec<NodeVariance>> {
+        let scenario = self.find_scenario(scenario_id).await?;
+
+        let nodes = self.node_repo.find_by_scenario_id(scenario_id).await?;
+        if let Some(id) = node_id
+            && !nodes.iter().any(|n| n.id == id)
+        {
+            return Err(anyhow::anyhow!("Node not found"));
+        }
+
+        let services: HashMap<Uuid, Option<Uuid>> =
+            nodes.iter().map(|n| (n.id, n.service_id)).collect();
+
+        let mut entries = self.entry_repo.find_by_scenario_id(scenario_id).await?;
+        entries.retain(|e| {
+            from.is_none_or(|from| e.target_month >= from)
+                && to.is_none_or(|to| e.target_month <= to)
+                && service_id
+                    .is_none_or(|id| services.get(&e.node_id).copied().flatten() == Some(id))
+        });
+        let labels = self.bucket_entries(&scenario, &mut entries, period).await?;
+
+        let parents = pl_reports::parent_map(&nodes);
+        let mut rollups: Vec<NodeRollup> = pl_reports::rollup(&nodes, &entries)
+            .into_iter()
+            .filter(|r| {
+                node_id.is_none_or(|id| pl_reports::ancestors_of(r.node_id, &parents).contains(&id))
+            })
+            .collect();
+
+        for cell in rollups.iter_mut().flat_map(|r| r.cells.iter_mut()) {
+            cell.period = labels.get(&cell.target_month).cloned();
+        }
+
+        let account_types = self.account_types().await?;
+
+        Ok(pl_reports::variance(rollups, &account_types))
+    }
+
+    /// 2つのシナリオをlineage_idで突き合わせて比較する
+    pub async fn compare(
+        &self,
+        base_scenario_id: Uuid,
+        target_scenario_id: Uuid,
+    ) -> anyhow::Result<Vec<NodeComparison>> {
+        let base = self.rollup(base_scenario_id, None).await?;
+        let target = self.rollup(target_scenario_id, None).await?;
+
+        Ok(pl_reports::compare(base, target))
+    }
+
+    /// 分岐シナリオを分岐元と比較する (分岐元がbase)
+    pub async fn compare_with_parent(
+        &self,
+        scenario_id: Uuid,
+    ) -> anyhow::Result<Vec<NodeComparison>> {
+        let scenario = self.find_scenario(scenario_id).await?;
+        let parent_id = scenario.parent_scenario_id.ok_or_else(|| {
+            anyhow::anyhow!("Scenario must be a branch to compare with its parent")
+        })?;
+
+        self.compare(parent_id, scenario_id).await
+    }
+
+    /// plan_nodesのservice_idを通してサービスごとのP/Lを作成する
+    pub async fn service_pl(
+        &self,
+        scenario_id: Uuid,
+        service_id: Option<Uuid>,
+        period: Option<PeriodGranularity>,
+    ) -> anyhow::Result<Vec<ServicePl>> {
+        let scenario = self.find_scenario(scenario_id).await?;
+
+        let mut services = self.service_repo.find_all().await?;
+        if let Some(id) = service_id {
+            services.retain(|s| s.id == id);
+            if services.is_empty() {
+                return Err(anyhow::anyhow!("Service not found"));
+            }
+        }
+
+        let nodes = self.node_repo.find_by_scenario_id(scenario_id).await?;
+        let mut entries = self.entry_repo.find_by_scenario_id(scenario_id).await?;
+        let labels = self.bucket_entries(&scenario, &mut entries, period).await?;
+        let account_types = self.account_types().await?;
+
+        let mut report = pl_reports::service_pl(&services, &nodes, &entries, &account_types);
+        for month in report.iter_mut().flat_map(|s| s.months.iter_mut()) {
+            month.period = labels.get(&month.target_month).cloned();
+        }
+
+        Ok(report)
+    }
+
+    /// 締め済みの月は確定値、cutoff以降の月は計画値を使った着地見込みを計算する
+    pub async fn forecast(&self, scenario_id: Uuid, cutoff: NaiveDate) -> anyhow::Result<Forecast> {
+        if cutoff.day() != 1 {
+            return Err(anyhow::anyhow!("Cutoff must be the first day of a month"));
+        }
+
+        self.find_scenario(scenario_id).await?;
+
+        let entries = self.entry_repo.find_by_scenario_id(scenario_id).await?;
+        let account_types = self.account_types().await?;
+
+        Ok(pl_reports::forecast(&entries, cutoff, &account_types))
+    }
+
+    /// 実体ノード × 科目 × 月のグリッドを作成する
+    /// 列はシナリオ期間の全ての月で、未入力のセルも明示的に含める
+    pub async fn grid(&self, scenario_id: Uuid, category: EntryCategory) -> anyhow::Result<PlGrid> {
+        let scenario = self.find_scenario(scenario_id).await?;
+
+        let nodes = self.node_repo.find_by_scenario_id(scenario_id).await?;
+        let entries = self.entry_repo.find_by_scenario_id(scenario_id).await?;
+        let account_items = self.account_item_repo.find_all().await?;
+
+        Ok(pl_reports::pl_grid(
+            &nodes,
+            &account_items,
+            &entries,
+            category,
+            scenario.months(),
+        ))
+    }
+
+    /// シナリオのツリー・科目・集計値をまとめて取得する
+    /// 列はシナリオ期間の月、期間単位が指定された場合は会計期間
+    pub async fn export_data(
+        &self,
+        scenario_id: Uuid,
+        period: Option<PeriodGranularity>,
+    ) -> anyhow::Result<ScenarioExport> {
+        let scenario = self.find_scenario(scenario_id).await?;
+
+        let columns = match period {
+            None => scenario
+                .months()
+                .into_iter()
+                .map(|month| ReportColumn {
+                    month,
+                    label: month.format("%Y-%m").to_string(),
+                })
+                .collect(),
+            Some(granularity) => {
+                let calendar = self.fiscal_calendar_repo.find().await?;
+                let mut columns: Vec<ReportColumn> = Vec::new();
+                for month in scenario.months() {
+                    let fiscal_period = calendar.period_of(month, granularity);
+                    if columns.last().map(|c| c.month) != Some(fiscal_period.start_month) {
+                        columns.push(ReportColumn {
+                            month: fiscal_period.start_month,
+                            label: fiscal_period.label,
+                        });
+                    }
+                }
+                columns
+            }
+        };
+
+        let nodes = self.node_repo.find_by_scenario_id(scenario_id).await?;
+        let account_items = self.account_item_repo.find_all().await?;
+        let rollups = self.rollup(scenario_id, period).await?;
+
+        Ok(ScenarioExport {
+            scenario,
+            nodes,
+            account_items,
+            rollups,
+            columns,
+        })
+    }
+}
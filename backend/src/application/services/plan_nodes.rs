@@ -1,43 +1,105 @@
+use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::domain::plan_nodes::{NodeType, PlanNode, PlanNodeRepository, UpdatePlanNodeParams};
-use crate::domain::scenarios::ScenarioRepository;
+use crate::domain::pl_entries::PlEntryRepository;
+use crate::domain::plan_nodes::{
+    self, NodeType, PlanNode, PlanNodeRepository, PlanNodeTree, TrashedPlanNode,
+    UpdatePlanNodeParams,
+};
+use crate::domain::scenarios::{Scenario, ScenarioRepository};
+use crate::domain::subtree_copies::{self, SubtreeCopyOptions, SubtreeCopySummary};
+use crate::domain::user::UserRole;
 use crate::presentation::dtos::UpdatePlanNodeRequest;
 
-pub struct PlanNodeService<P, S> {
+// Memberは担当するノード (祖先が担当する場合を含む) の下のみ編集できる
+// 自分の分岐シナリオは担当に関わらず編集でき、取り込む際に確認する
+// node_idがNoneの場合はRootへの追加・移動
+fn ensure_assigned(
+    scenario: &Scenario,
+    nodes: &[PlanNode],
+    node_id: Option<Uuid>,
+    user_id: Uuid,
+    role: &UserRole,
+) -> anyhow::Result<()> {
+    if !plan_nodes::can_edit_in(scenario, nodes, node_id, user_id, role) {
+        return Err(anyhow::anyhow!(
+            "担当外のノードは編集できません（Read-Only）"
+        ));
+    }
+
+    Ok(())
+}
+
+pub struct PlanNodeService<P, S, E> {
+    pool: PgPool,
     plan_node_repo: P,
     scenario_repo: S,
+    entry_repo: E,
 }
 
-impl<P, S> PlanNodeService<P, S>
+impl<P, S, E> PlanNodeService<P, S, E>
 where
     P: PlanNodeRepository,
     S: ScenarioRepository,
+    E: PlEntryRepository,
 {
-    pub fn new(plan_node_repo: P, scenario_repo: S) -> Self {
+    pub fn new(pool: PgPool, plan_node_repo: P, scenario_repo: S, entry_repo: E) -> Self {
         Self {
+            pool,
             plan_node_repo,
             scenario_repo,
+            entry_repo,
         }
     }
 
-    async fn ensure_scenario_is_writable(&self, scenario_id: Uuid) -> anyhow::Result<()> {
+    async fn ensure_scenario_is_writable(
+        &self,
+        scenario_id: Uuid,
+        user_id: Uuid,
+    ) -> anyhow::Result<Scenario> {
         let scenario = self
             .scenario_repo
             .find_by_id(scenario_id)
             .await?
             .ok_or_else(|| anyhow::anyhow!("Scenario not found"))?;
 
-        if !scenario.is_current {
+        if scenario.is_locked {
             return Err(anyhow::anyhow!(
-                "作成中のシナリオ以外は編集できません（Read-Only）"
+                "締め済みのシナリオは編集できません（Read-Only）"
             ));
         }
 
-        Ok(())
+        if !scenario.status.is_editable() {
+            return Err(anyhow::anyhow!(
+                "提出済み・承認済みのシナリオは編集できません（Read-Only）"
+            ));
+        }
+
+        if !scenario.is_editable_by(user_id) {
+            return Err(anyhow::anyhow!(
+                "作成中のシナリオと自分の分岐シナリオ以外は編集できません（Read-Only）"
+            ));
+        }
+
+        Ok(scenario)
     }
 
-    #[allow(clippy::too_man_arguments)]
+    async fn ensure_node_is_assigned(
+        &self,
+        scenario: &Scenario,
+        node_id: Option<Uuid>,
+        user_id: Uuid,
+        role: &UserRole,
+    ) -> anyhow::Result<()> {
+        let nodes = match node_id {
+            Some(id) => self.plan_node_repo.find_with_ancestors(id).await?,
+            None => Vec::new(),
+        };
+
+        ensure_assigned(scenario, &nodes, node_id, user_id, role)
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub async fn create(
         &self,
         scenario_id: Uuid,
@@ -48,8 +110,11 @@ where
         display_order: i32,
         service_id: Option<Uuid>,
         user_id: Uuid,
+        role: &UserRole,
     ) -> anyhow::Result<PlanNode> {
-        self.ensure_scenario_is_writable(scenario_id).await?;
+        let scenario = self
+            .ensure_scenario_is_writable(scenario_id, user_id)
+            .await?;
 
         // 親Nodeがある場合のバリデーション
         if let Some(pid) = parent_id {
@@ -76,6 +141,9 @@ where
             }
         }
 
+        self.ensure_node_is_assigned(&scenario, parent_id, user_id, role)
+            .await?;
+
         // ドメインモデルの生成
         let new_node = PlanNode::new(
             scenario_id,
@@ -102,11 +170,37 @@ where
         self.plan_node_repo.find_by_scenario_id(scenario_id).await
     }
 
+    /// 入れ子のツリーを返す
+    pub async fn tree(
+        &self,
+        scenario_id: Uuid,
+        root_id: Option<Uuid>,
+        max_depth: Option<u32>,
+    ) -> anyhow::Result<Vec<PlanNodeTree>> {
+        self.scenario_repo
+            .find_by_id(scenario_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Scenario not found"))?;
+
+        let max_depth = max_depth.map(|d| i32::try_from(d).unwrap_or(i32::MAX));
+        let rows = self
+            .plan_node_repo
+            .find_tree(scenario_id, root_id, max_depth)
+            .await?;
+
+        if root_id.is_some() && rows.is_empty() {
+            return Err(anyhow::anyhow!("Root node not found in the scenario"));
+        }
+
+        Ok(plan_nodes::build_tree(rows))
+    }
+
     pub async fn update(
         &self,
         id: Uuid,
         req: UpdatePlanNodeRequest,
         updated_by: Uuid,
+        role: &UserRole,
     ) -> anyhow::Result<PlanNode> {
         let current_node = self
             .plan_node_repo
@@ -114,25 +208,372 @@ where
             .await?
             .ok_or_else(|| anyhow::anyhow!("PlanNode not found"))?;
 
-        self.ensure_scenario_is_writable(current_node.scenario_id)
+        let scenario = self
+            .ensure_scenario_is_writable(current_node.scenario_id, updated_by)
+            .await?;
+        self.ensure_node_is_assigned(&scenario, Some(id), updated_by, role)
             .await?;
 
         // DTO から Domain Paramsへ変換
         let params: UpdatePlanNodeParams = req.into();
 
-        self.plan_node_repo.update(id, params, updated_by).await
+        let mut tx = self.pool.begin().await?;
+        let updated = self
+            .plan_node_repo
+            .update(&mut tx, id, params, updated_by)
+            .await?;
+        tx.commit().await?;
+
+        Ok(updated)
+    }
+
+    /// ノードを別の親の下 (parent_idがNoneの場合はRoot) に移動する
+    /// lineage_idとEntryはそのまま残り、子孫ノードも一緒に移動する
+    pub async fn move_node(
+        &self,
+        id: Uuid,
+        parent_id: Option<Uuid>,
+        display_order: Option<i32>,
+        updated_by: Uuid,
+        role: &UserRole,
+    ) -> anyhow::Result<PlanNode> {
+        let current_node = self
+            .plan_node_repo
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("PlanNode not found"))?;
+
+        let scenario = self
+            .ensure_scenario_is_writable(current_node.scenario_id, updated_by)
+            .await?;
+
+        let mut tx = self.pool.begin().await?;
+
+        let nodes = self
+            .plan_node_repo
+            .find_by_scenario_id_for_update(&mut tx, current_node.scenario_id)
+            .await?;
+        let node = nodes
+            .iter()
+            .find(|n| n.id == id)
+            .ok_or_else(|| anyhow::anyhow!("PlanNode not found"))?;
+
+        // 移動元と移動先の両方を担当している必要がある
+        ensure_assigned(&scenario, &nodes, Some(id), updated_by, role)?;
+        ensure_assigned(&scenario, &nodes, parent_id, updated_by, role)?;
+
+        match parent_id {
+            Some(pid) => {
+                // 同じシナリオのノードのみ親にできる
+                let parent = nodes
+                    .iter()
+                    .find(|n| n.id == pid)
+                    .ok_or_else(|| anyhow::anyhow!("Parent node not found in the same scenario"))?;
+
+                // 自身や子孫の下に移動すると循環する
+                if plan_nodes::subtree_ids(&nodes, id).contains(&pid) {
+                    return Err(anyhow::anyhow!(
+                        "Cannot move a node under itself or its descendants"
+                    ));
+                }
+
+                if !node.node_type.can_be_child_of(&parent.node_type) {
+                    return Err(anyhow::anyhow!(
+                        "Node type '{:?}' cannot be a child of '{:?}'",
+                        node.node_type,
+                        parent.node_type
+                    ));
+                }
+            }
+            None => {
+                if !node.node_type.can_be_root() {
+                    return Err(anyhow::anyhow!("Only 'Initiative' can be a root node"));
+                }
+            }
+        }
+
+        let moved = self
+            .plan_node_repo
+            .move_to(
+                &mut tx,
+                id,
+                parent_id,
+                display_order.unwrap_or(node.display_order),
+                updated_by,
+            )
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(moved)
+    }
+
+    /// ノードと子孫を同じシナリオのparent_idの下 (Noneの場合はRoot) に複製する
+    pub async fn copy_subtree(
+        &self,
+        id: Uuid,
+        parent_id: Option<Uuid>,
+        options: SubtreeCopyOptions,
+        created_by: Uuid,
+        role: &UserRole,
+    ) -> anyhow::Result<SubtreeCopySummary> {
+        if options.title.as_ref().is_some_and(|t| t.trim().is_empty()) {
+            return Err(anyhow::anyhow!("Title cannot be empty"));
+        }
+
+        let source = self
+            .plan_node_repo
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("PlanNode not found"))?;
+
+        let scenario = self
+            .ensure_scenario_is_writable(source.scenario_id, created_by)
+            .await?;
+
+        let mut tx = self.pool.begin().await?;
+
+        let nodes = self
+            .plan_node_repo
+            .find_by_scenario_id_for_update(&mut tx, source.scenario_id)
+            .await?;
+        let node = nodes
+            .iter()
+            .find(|n| n.id == id)
+            .ok_or_else(|| anyhow::anyhow!("PlanNode not found"))?;
+
+        ensure_assigned(&scenario, &nodes, parent_id, created_by, role)?;
+
+        match parent_id {
+            Some(pid) => {
+                let parent = nodes
+                    .iter()
+                    .find(|n| n.id == pid)
+                    .ok_or_else(|| anyhow::anyhow!("Parent node not found in the same scenario"))?;
+
+                if !node.node_type.can_be_child_of(&parent.node_type) {
+                    return Err(anyhow::anyhow!(
+                        "Node type '{:?}' cannot be a child of '{:?}'",
+                        node.node_type,
+                        parent.node_type
+                    ));
+                }
+            }
+            None => {
+                if !node.node_type.can_be_root() {
+                    return Err(anyhow::anyhow!("Only 'Initiative' can be a root node"));
+                }
+            }
+        }
+
+        let entries = if options.copy_entries {
+            let ids = plan_nodes::subtree_ids(&nodes, id);
+            self.entry_repo
+                .find_by_node_ids(ids.into_iter().collect())
+                .await?
+        } else {
+            Vec::new()
+        };
+
+        let plan = subtree_copies::plan(
+            &scenario, &nodes, &entries, id, parent_id, &options, created_by,
+        );
+        let summary = SubtreeCopySummary {
+            root: plan.nodes[0].clone(),
+            node_count: plan.nodes.len(),
+            entry_count: plan.entries.len(),
+            dropped_entries: plan.dropped_entries,
+        };
+
+        self.plan_node_repo.create_many(&mut tx, plan.nodes).await?;
+        self.entry_repo.create_many(&mut tx, plan.entries).await?;
+
+        tx.commit().await?;
+
+        Ok(summary)
     }
 
-    pub async fn delete(&self, id: Uuid) -> anyhow::Result<()> {
+    /// ノードを論理削除する
+    /// 子ノードがある場合はcascadeを指定したときのみ子孫ごと削除する
+    /// Entryは削除せず、ノードと一緒に集計から外れる (復元すると元に戻る)
+    pub async fn delete(
+        &self,
+        id: Uuid,
+        cascade: bool,
+        deleted_by: Uuid,
+        role: &UserRole,
+    ) -> anyhow::Result<()> {
         let current_node = self
             .plan_node_repo
             .find_by_id(id)
             .await?
             .ok_or_else(|| anyhow::anyhow!("PlanNode not found"))?;
 
-        self.ensure_scenario_is_writable(current_node.scenario_id)
+        let scenario = self
+            .ensure_scenario_is_writable(current_node.scenario_id, deleted_by)
+            .await?;
+
+        let mut tx = self.pool.begin().await?;
+
+        let nodes = self
+            .plan_node_repo
+            .find_by_scenario_id_for_update(&mut tx, current_node.scenario_id)
+            .await?;
+        ensure_assigned(&scenario, &nodes, Some(id), deleted_by, role)?;
+        let ids = plan_nodes::subtree_ids(&nodes, id);
+
+        if ids.len() > 1 && !cascade {
+            return Err(anyhow::anyhow!(
+                "子ノードが存在するため削除できません。子孫ごと削除する場合はcascadeを指定してください。"
+            ));
+        }
+
+        self.plan_node_repo
+            .delete(&mut tx, ids.into_iter().collect(), deleted_by)
             .await?;
 
-        self.plan_node_repo.delete(id).await
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// 削除済みのノードを、同じ操作で削除された子孫と一緒に復元する
+    pub async fn restore(
+        &self,
+        id: Uuid,
+        restored_by: Uuid,
+        role: &UserRole,
+    ) -> anyhow::Result<PlanNode> {
+        let deleted_node = self
+            .plan_node_repo
+            .find_deleted_by_id(id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Deleted plan node not found"))?;
+
+        let scenario = self
+            .ensure_scenario_is_writable(deleted_node.scenario_id, restored_by)
+            .await?;
+
+        let mut tx = self.pool.begin().await?;
+
+        let mut nodes = self
+            .plan_node_repo
+            .find_by_scenario_id_for_update(&mut tx, deleted_node.scenario_id)
+            .await?;
+        if let Some(pid) = deleted_node.parent_id
+            && !nodes.iter().any(|n| n.id == pid)
+        {
+            return Err(anyhow::anyhow!(
+                "Parent node is deleted: restore the parent first"
+            ));
+        }
+        nodes.push(deleted_node.clone());
+        ensure_assigned(&scenario, &nodes, Some(id), restored_by, role)?;
+
+        let deleted = self
+            .plan_node_repo
+            .find_deleted_by_scenario_id(deleted_node.scenario_id)
+            .await?;
+        let ids: Vec<Uuid> = plan_nodes::deleted_together(&deleted, id)
+            .into_iter()
+            .collect();
+
+        // 同じlineage_idのノードが既に存在する場合は戻せない (マージ等で作り直された場合)
+        if let Some(node) = deleted.iter().filter(|n| ids.contains(&n.id)).find(|d| {
+            nodes
+                .iter()
+                .any(|n| n.id != d.id && n.lineage_id == d.lineage_id)
+        }) {
+            return Err(anyhow::anyhow!(
+                "A live node with the same lineage as '{}' already exists",
+                node.title
+            ));
+        }
+
+        let restored = self
+            .plan_node_repo
+            .restore(&mut tx, ids.clone(), restored_by)
+            .await?;
+        if restored != ids.len() as u64 {
+            return Err(anyhow::anyhow!(
+                "Plan node has been changed by another user"
+            ));
+        }
+
+        tx.commit().await?;
+
+        self.plan_node_repo
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("PlanNode not found"))
+    }
+
+    /// ノードの担当者を設定する (Manager・Adminのみ)
+    /// 担当者は子孫ノードにも引き継がれる
+    pub async fn update_assignees(
+        &self,
+        id: Uuid,
+        owner_id: Option<Uuid>,
+        editor_ids: Vec<Uuid>,
+        updated_by: Uuid,
+        role: &UserRole,
+    ) -> anyhow::Result<PlanNode> {
+        if !matches!(role, UserRole::Admin | UserRole::Manager) {
+            return Err(anyhow::anyhow!("Permission denied"));
+        }
+
+        let current_node = self
+            .plan_node_repo
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("PlanNode not found"))?;
+
+        self.ensure_scenario_is_writable(current_node.scenario_id, updated_by)
+            .await?;
+
+        let mut editor_ids = editor_ids;
+        editor_ids.sort();
+        editor_ids.dedup();
+
+        self.plan_node_repo
+            .update_assignees(id, owner_id, editor_ids, updated_by)
+            .await
+    }
+
+    /// シナリオのゴミ箱 (まとめて削除したノードの起点ごと、新しい順)
+    pub async fn list_trash(&self, scenario_id: Uuid) -> anyhow::Result<Vec<TrashedPlanNode>> {
+        let deleted = self
+            .plan_node_repo
+            .find_deleted_by_scenario_id(scenario_id)
+            .await?;
+        let entries = self
+            .entry_repo
+            .find_by_node_ids(deleted.iter().map(|n| n.id).collect())
+            .await?;
+
+        let mut trash = Vec::new();
+        for node in &deleted {
+            // 親と一緒に削除されたノードは親の件に含める
+            let deleted_with_parent = deleted
+                .iter()
+                .any(|p| Some(p.id) == node.parent_id && p.deleted_at == node.deleted_at);
+            if deleted_with_parent {
+                continue;
+            }
+            let Some(deleted_at) = node.deleted_at else {
+                continue;
+            };
+
+            let ids = plan_nodes::deleted_together(&deleted, node.id);
+            trash.push(TrashedPlanNode {
+                node: node.clone(),
+                deleted_at,
+                deleted_by: node.deleted_by,
+                node_count: ids.len(),
+                entry_count: entries.iter().filter(|e| ids.contains(&e.node_id)).count(),
+            });
+        }
+
+        Ok(trash)
     }
 }
@@ -0,0 +1,468 @@
+use chrono::Utc;
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::application::services::pl_entries::PlEntryService;
+use crate::domain::account_items::AccountItemRepository;
+use crate::domain::closed_months::ClosedMonthRepository;
+use crate::domain::history::PlEntryHistoryRepository;
+use crate::domain::ledger_mappings::LedgerMappingRepository;
+use crate::domain::pl_entries::{PlEntry, PlEntryRepository};
+use crate::domain::plan_nodes::{self, PlanNode, PlanNodeRepository, UpdatePlanNodeParams};
+use crate::domain::scenario_merges::{self, MergeChange, MergeChangeKind, MergeReport, MergeSide};
+use crate::domain::scenarios::{Scenario, ScenarioRepository};
+use crate::domain::user::UserRole;
+
+/// 分岐シナリオを分岐元に取り込む
+/// Entryの保存は通常の編集と同じ処理 (締め月のチェック・履歴の記録) を通す
+pub struct ScenarioMergeService<R, N, H, S, A, M, C>
+where
+    R: PlEntryRepository,
+    N: PlanNodeRepository,
+    H: PlEntryHistoryRepository,
+    S: ScenarioRepository,
+    A: AccountItemRepository,
+    M: LedgerMappingRepository,
+    C: ClosedMonthRepository,
+{
+    pool: PgPool,
+    entry_repo: R,
+    node_repo: N,
+    history_repo: H,
+    scenario_repo: S,
+    entry_service: PlEntryService<R, N, H, S, A, M, C>,
+}
+
+impl<R, N, H, S, A, M, C> ScenarioMergeService<R, N, H, S, A, M, C>
+where
+    R: PlEntryRepository,
+    N: PlanNodeRepository,
+    H: PlEntryHistoryRepository,
+    S: ScenarioRepository,
+    A: AccountItemRepository,
+    M: LedgerMappingRepository,
+    C: ClosedMonthRepository,
+{
+    pub fn new(
+        pool: PgPool,
+        entry_repo: R,
+        node_repo: N,
+        history_repo: H,
+        scenario_repo: S,
+        entry_service: PlEntryService<R, N, H, S, A, M, C>,
+    ) -> Self {
+        Self {
+            pool,
+            entry_repo,
+            node_repo,
+            history_repo,
+            scenario_repo,
+            entry_service,
+        }
+    }
+
+    // 分岐シナリオと分岐元 (取り込み先) のノード・Entryを読み込み、差分を取る
+    async fn merge_context(&self, branch_id: Uuid) -> anyhow::Result<MergeContext> {
+        let branch = self
+            .scenario_repo
+            .find_by_id(branch_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Scenario not found"))?;
+        let parent_id = branch
+            .parent_scenario_id
+            .ok_or_else(|| anyhow::anyhow!("Scenario must be a branch to merge into its parent"))?;
+        let target = self
+            .scenario_repo
+            .find_by_id(parent_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Parent scenario not found"))?;
+
+        let base_nodes = self.node_repo.find_by_scenario_id(target.id).await?;
+        let base_entries = self.entry_repo.find_by_scenario_id(target.id).await?;
+        let branch_nodes = self.node_repo.find_by_scenario_id(branch.id).await?;
+        let branch_entries = self.entry_repo.find_by_scenario_id(branch.id).await?;
+
+        // 分岐時のコピーは履歴を書かないので、履歴の有無で分岐後に変更されたEntryを判定する
+        let base_changed: HashSet<Uuid> = self
+            .history_repo
+            .find_by_entry_ids(base_entries.iter().map(|e| e.id).collect())
+            .await?
+            .into_iter()
+            .filter(|h| h.changed_at > branch.created_at)
+            .map(|h| h.entry_id)
+            .collect();
+        let branch_changed: HashSet<Uuid> = self
+            .history_repo
+            .find_by_entry_ids(branch_entries.iter().map(|e| e.id).collect())
+            .await?
+            .into_iter()
+            .map(|h| h.entry_id)
+            .collect();
+
+        let changes = scenario_merges::diff(
+            &branch,
+            MergeSide {
+                nodes: &base_nodes,
+                entries: &base_entries,
+                changed_entry_ids: &base_changed,
+            },
+            MergeSide {
+                nodes: &branch_nodes,
+                entries: &branch_entries,
+                changed_entry_ids: &branch_changed,
+            },
+        );
+
+        Ok(MergeContext {
+            branch,
+            target,
+            base_nodes,
+            branch_nodes,
+            branch_entries,
+            changes,
+        })
+    }
+
+    /// 分岐シナリオを分岐元に取り込む場合の差分を返す
+    pub async fn merge_preview(&self, branch_id: Uuid) -> anyhow::Result<Vec<MergeChange>> {
+        Ok(self.merge_context(branch_id).await?.changes)
+    }
+
+    /// 選択した差分を分岐元に取り込む
+    /// 衝突している差分はoverwrite_conflictsを指定した場合のみ取り込み、それ以外は結果に含めて返す
+    pub async fn merge_branch(
+        &self,
+        branch_id: Uuid,
+        keys: Vec<String>,
+        overwrite_conflicts: bool,
+        user_id: Uuid,
+        role: &UserRole,
+    ) -> anyhow::Result<MergeReport> {
+        let ctx = self.merge_context(branch_id).await?;
+
+        if ctx.target.is_locked {
+            return Err(anyhow::anyhow!(
+                "Read-Only: Locked scenarios cannot be edited"
+            ));
+        }
+        if !ctx.target.status.is_editable() {
+            return Err(anyhow::anyhow!(
+                "Read-Only: Submitted or approved scenarios cannot be edited"
+            ));
+        }
+        if !ctx.target.is_editable_by(user_id) {
+            return Err(anyhow::anyhow!(
+                "Read-Only: Only the current scenario or your own branch can be edited"
+            ));
+        }
+
+        let mut selected: HashSet<String> = keys.into_iter().collect();
+        let mut applied = Vec::new();
+        let mut conflicts = Vec::new();
+        for change in ctx.changes {
+            if !selected.remove(&change.key) {
+                continue;
+            }
+            if change.conflict && !overwrite_conflicts {
+                conflicts.push(change);
+            } else {
+                applied.push(change);
+            }
+        }
+        if let Some(key) = selected.into_iter().next() {
+            return Err(anyhow::anyhow!("Unknown merge change: {}", key));
+        }
+
+        // Memberは担当するノードの下の差分のみ取り込める
+        for change in &applied {
+            let anchor = scenario_merges::target_anchor(
+                &ctx.base_nodes,
+                &ctx.branch_nodes,
+                change.lineage_id,
+            );
+            if !plan_nodes::can_edit_in(&ctx.target, &ctx.base_nodes, anchor, user_id, role) {
+                return Err(anyhow::anyhow!(
+                    "Read-Only: You are not assigned to node '{}'",
+                    change.title
+                ));
+            }
+
+            // 移動先の親も担当している必要がある
+            if change.kind == MergeChangeKind::NodeMoved {
+                let parent_anchor = change.parent_lineage_id.and_then(|lineage_id| {
+                    scenario_merges::target_anchor(&ctx.base_nodes, &ctx.branch_nodes, lineage_id)
+                });
+                if !plan_nodes::can_edit_in(
+                    &ctx.target,
+                    &ctx.base_nodes,
+                    parent_anchor,
+                    user_id,
+                    role,
+                ) {
+                    return Err(anyhow::anyhow!(
+                        "Read-Only: You are not assigned to the new parent of node '{}'",
+                        change.title
+                    ));
+                }
+            }
+        }
+
+        let branch_by_lineage: HashMap<Uuid, &PlanNode> =
+            ctx.branch_nodes.iter().map(|n| (n.lineage_id, n)).collect();
+        let branch_lineages: HashMap<Uuid, Uuid> = ctx
+            .branch_nodes
+            .iter()
+            .map(|n| (n.id, n.lineage_id))
+            .collect();
+        // lineage_id → 取り込み先のノードID
+        let mut target_ids: HashMap<Uuid, Uuid> = ctx
+            .base_nodes
+            .iter()
+            .map(|n| (n.lineage_id, n.id))
+            .collect();
+        let target_node_id = |target_ids: &HashMap<Uuid, Uuid>, lineage_id: Uuid| {
+            target_ids
+                .get(&lineage_id)
+                .copied()
+                .ok_or_else(|| anyhow::anyhow!("Node not found in parent scenario"))
+        };
+
+        // トランザクション開始
+        let mut tx = self.pool.begin().await?;
+
+        // 追加 (親から順に並んでいる)
+        for change in applied
+            .iter()
+            .filter(|c| c.kind == MergeChangeKind::NodeAdded)
+        {
+            let node = branch_by_lineage[&change.lineage_id];
+            let parent_id = match node.parent_id {
+                Some(pid) => {
+                    let parent_lineage = branch_lineages[&pid];
+                    Some(target_ids.get(&parent_lineage).copied().ok_or_else(|| {
+                        anyhow::anyhow!("Parent of node '{}' must be merged first", node.title)
+                    })?)
+                }
+                None => None,
+            };
+
+            let new_node = PlanNode {
+                id: Uuid::new_v4(),
+                scenario_id: ctx.target.id,
+                parent_id,
+                lineage_id: node.lineage_id,
+                title: node.title.clone(),
+                description: node.description.clone(),
+                node_type: node.node_type.clone(),
+                display_order: node.display_order,
+                service_id: node.service_id,
+                owner_id: node.owner_id,
+                editor_ids: node.editor_ids.clone(),
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                created_by: user_id,
+                updated_by: user_id,
+                deleted_at: None,
+                deleted_by: None,
+            };
+            let new_id = new_node.id;
+            self.node_repo.create_many(&mut tx, vec![new_node]).await?;
+            target_ids.insert(node.lineage_id, new_id);
+
+            for entry in ctx.branch_entries.iter().filter(|e| e.node_id == node.id) {
+                self.entry_service
+                    .save_entry_logic(
+                        &mut tx,
+                        new_id,
+                        entry.account_item_id,
+                        entry.target_month,
+                        entry.entry_category.clone(),
+                        entry.amount,
+                        entry.description.clone(),
+                        user_id,
+                        "Merge",
+                    )
+                    .await?;
+            }
+        }
+
+        // 移動 (追加したノードの下への移動もあるので追加の後に行う)
+        let moves: Vec<&MergeChange> = applied
+            .iter()
+            .filter(|c| c.kind == MergeChangeKind::NodeMoved)
+            .collect();
+        if !moves.is_empty() {
+            let mut current = self
+                .node_repo
+                .find_by_scenario_id_for_update(&mut tx, ctx.target.id)
+                .await?;
+
+            for change in moves {
+                let node_id = target_node_id(&target_ids, change.lineage_id)?;
+                let node = current
+                    .iter()
+                    .find(|n| n.id == node_id)
+                    .ok_or_else(|| anyhow::anyhow!("Node not found in parent scenario"))?;
+                let parent_id = match change.parent_lineage_id {
+                    Some(lineage_id) => {
+                        Some(target_ids.get(&lineage_id).copied().ok_or_else(|| {
+                            anyhow::anyhow!("Parent of node '{}' must be merged first", node.title)
+                        })?)
+                    }
+                    None => None,
+                };
+
+                match parent_id {
+                    Some(pid) => {
+                        let parent = current
+                            .iter()
+                            .find(|n| n.id == pid)
+                            .ok_or_else(|| anyhow::anyhow!("Node not found in parent scenario"))?;
+
+                        // 分岐元で行われた移動と合わせると循環する
+                        if plan_nodes::subtree_ids(&current, node_id).contains(&pid) {
+                            return Err(anyhow::anyhow!(
+                                "Merge conflict: moving node '{}' under '{}' would create a cycle",
+                                node.title,
+                                parent.title
+                            ));
+                        }
+                        if !node.node_type.can_be_child_of(&parent.node_type) {
+                            return Err(anyhow::anyhow!(
+                                "Node type '{:?}' cannot be a child of '{:?}'",
+                                node.node_type,
+                                parent.node_type
+                            ));
+                        }
+                    }
+                    None => {
+                        if !node.node_type.can_be_root() {
+                            return Err(anyhow::anyhow!("Only 'Initiative' can be a root node"));
+                        }
+                    }
+                }
+
+                let moved = self
+                    .node_repo
+                    .move_to(
+                        &mut tx,
+                        node_id,
+                        parent_id,
+                        branch_by_lineage[&change.lineage_id].display_order,
+                        user_id,
+                    )
+                    .await?;
+                if let Some(n) = current.iter_mut().find(|n| n.id == node_id) {
+                    *n = moved;
+                }
+            }
+        }
+
+        for change in &applied {
+            match change.kind {
+                MergeChangeKind::NodeRenamed => {
+                    let params = UpdatePlanNodeParams {
+                        title: Some(change.title.clone()),
+                        description: None,
+                        display_order: None,
+                    };
+                    self.node_repo
+                        .update(
+                            &mut tx,
+                            target_node_id(&target_ids, change.lineage_id)?,
+                            params,
+                            user_id,
+                        )
+                        .await?;
+                }
+                MergeChangeKind::AmountChanged => {
+                    let node = branch_by_lineage[&change.lineage_id];
+                    let entry = ctx
+                        .branch_entries
+                        .iter()
+                        .find(|e| {
+                            e.node_id == node.id
+                                && Some(e.account_item_id) == change.account_item_id
+                                && Some(e.target_month) == change.target_month
+                                && Some(&e.entry_category) == change.entry_category.as_ref()
+                        })
+                        .ok_or_else(|| anyhow::anyhow!("Entry not found in branch scenario"))?;
+
+                    self.entry_service
+                        .save_entry_logic(
+                            &mut tx,
+                            target_node_id(&target_ids, change.lineage_id)?,
+                            entry.account_item_id,
+                            entry.target_month,
+                            entry.entry_category.clone(),
+                            entry.amount,
+                            entry.description.clone(),
+                            user_id,
+                            "Merge",
+                        )
+                        .await?;
+                }
+                _ => {}
+            }
+        }
+
+        // 削除 (分岐シナリオに存在しないノードのみ論理削除する)
+        let mut removed_ids: Vec<Uuid> = Vec::new();
+        for change in applied
+            .iter()
+            .filter(|c| c.kind == MergeChangeKind::NodeRemoved)
+        {
+            removed_ids.push(target_node_id(&target_ids, change.lineage_id)?);
+        }
+        if !removed_ids.is_empty() {
+            // 削除するノードの下に残るノードがある場合は取り込まない
+            let current = self
+                .node_repo
+                .find_by_scenario_id_for_update(&mut tx, ctx.target.id)
+                .await?;
+            let titles: HashMap<Uuid, &str> =
+                current.iter().map(|n| (n.id, n.title.as_str())).collect();
+            for node in &current {
+                let Some(pid) = node.parent_id.filter(|pid| removed_ids.contains(pid)) else {
+                    continue;
+                };
+                if removed_ids.contains(&node.id) {
+                    continue;
+                }
+
+                // 分岐した後に分岐元で追加されたノードは他のユーザーの作業なので消さない
+                if node.created_at > ctx.branch.created_at {
+                    return Err(anyhow::anyhow!(
+                        "Merge conflict: node '{}' was added under '{}' after the branch was created",
+                        node.title,
+                        titles[&pid]
+                    ));
+                }
+                return Err(anyhow::anyhow!(
+                    "Removing node '{}' requires merging the change for its child node '{}' as well",
+                    titles[&pid],
+                    node.title
+                ));
+            }
+
+            // まとめて削除したノードとして復元できるよう、1回で論理削除する
+            self.node_repo.delete(&mut tx, removed_ids, user_id).await?;
+        }
+
+        // コミット
+        tx.commit().await?;
+
+        Ok(MergeReport { applied, conflicts })
+    }
+}
+
+// 分岐シナリオの取り込みに使うデータ
+struct MergeContext {
+    branch: Scenario,
+    target: Scenario,
+    base_nodes: Vec<PlanNode>,
+    branch_nodes: Vec<PlanNode>,
+    branch_entries: Vec<PlEntry>,
+    changes: Vec<MergeChange>,
+}
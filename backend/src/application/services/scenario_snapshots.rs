@@ -0,0 +1,359 @@
+use chrono::Utc;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::domain::account_items::AccountItemRepository;
+use crate::domain::history::{PlEntryHistory, PlEntryHistoryRepository};
+use crate::domain::pl_entries::{PlEntry, PlEntryRepository};
+use crate::domain::plan_nodes::{self, PlanNode, PlanNodeRepository};
+use crate::domain::scenario_snapshots::{
+    SNAPSHOT_FORMAT_VERSION, ScenarioSnapshot, SnapshotEntry, SnapshotHistory, SnapshotNode,
+    SnapshotScenario,
+};
+use crate::domain::scenarios::{Scenario, ScenarioRepository};
+use crate::domain::services::ServiceRepository;
+use crate::domain::user::UserRepository;
+
+pub struct ScenarioSnapshotService<S, N, E, H, A, V, U> {
+    pool: PgPool,
+    scenario_repo: S,
+    node_repo: N,
+    entry_repo: E,
+    history_repo: H,
+    account_item_repo: A,
+    service_repo: V,
+    user_repo: U,
+}
+
+impl<S, N, E, H, A, V, U> ScenarioSnapshotService<S, N, E, H, A, V, U>
+where
+    S: ScenarioRepository,
+    N: PlanNodeRepository,
+    E: PlEntryRepository,
+    H: PlEntryHistoryRepository,
+    A: AccountItemRepository,
+    V: ServiceRepository,
+    U: UserRepository,
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        pool: PgPool,
+        scenario_repo: S,
+        node_repo: N,
+        entry_repo: E,
+        history_repo: H,
+        account_item_repo: A,
+        service_repo: V,
+        user_repo: U,
+    ) -> Self {
+        Self {
+            pool,
+            scenario_repo,
+            node_repo,
+            entry_repo,
+            history_repo,
+            account_item_repo,
+            service_repo,
+            user_repo,
+        }
+    }
+
+    /// シナリオをノード・Entry・履歴ごとアーカイブにする
+    pub async fn export(&self, scenario_id: Uuid) -> anyhow::Result<ScenarioSnapshot> {
+        let scenario = self
+            .scenario_repo
+            .find_by_id(scenario_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Scenario not found"))?;
+
+        let service_slugs: HashMap<Uuid, String> = self
+            .service_repo
+            .find_all()
+            .await?
+            .into_iter()
+            .map(|s| (s.id, s.slug))
+            .collect();
+        let account_codes: HashMap<Uuid, String> = self
+            .account_item_repo
+            .find_all()
+            .await?
+            .into_iter()
+            .map(|item| (item.id, item.code))
+            .collect();
+
+        let nodes = self.node_repo.find_by_scenario_id(scenario_id).await?;
+        let mut snapshot_nodes = Vec::new();
+        // 取り込み時に親から順に作成できるよう、深さ優先の順で並べる
+        for (node, _) in plan_nodes::depth_first(&nodes) {
+            let service_slug = match node.service_id {
+                Some(id) => Some(
+                    service_slugs
+                        .get(&id)
+                        .cloned()
+                        .ok_or_else(|| anyhow::anyhow!("Service not found: {}", id))?,
+                ),
+                None => None,
+            };
+
+            snapshot_nodes.push(SnapshotNode {
+                id: node.id,
+                parent_id: node.parent_id,
+                lineage_id: node.lineage_id,
+                title: node.title.clone(),
+                description: node.description.clone(),
+                node_type: node.node_type.clone(),
+                display_order: node.display_order,
+                service_slug,
+            });
+        }
+
+        let entries = self.entry_repo.find_by_scenario_id(scenario_id).await?;
+        let histories = self
+            .history_repo
+            .find_by_entry_ids(entries.iter().map(|e| e.id).collect())
+            .await?;
+
+        let mut user_ids: Vec<Uuid> = histories.iter().map(|h| h.changed_by).collect();
+        user_ids.sort();
+        user_ids.dedup();
+        let emails: HashMap<Uuid, String> = self
+            .user_repo
+            .find_by_ids(user_ids)
+            .await?
+            .into_iter()
+            .map(|u| (u.id, u.email))
+            .collect();
+
+        let mut histories_by_entry: HashMap<Uuid, Vec<SnapshotHistory>> = HashMap::new();
+        for history in histories {
+            histories_by_entry
+                .entry(history.entry_id)
+                .or_default()
+                .push(SnapshotHistory {
+                    change_type: history.change_type,
+                    previous_amount: history.previous_amount,
+                    new_amount: history.new_amount,
+                    changed_at: history.changed_at,
+                    changed_by: emails.get(&history.changed_by).cloned(),
+                    operation_source: history.operation_source,
+                });
+        }
+
+        let mut snapshot_entries = Vec::new();
+        for entry in entries {
+            let account_item_code = account_codes
+                .get(&entry.account_item_id)
+                .cloned()
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Account item not found: {}", entry.account_item_id)
+                })?;
+
+            snapshot_entries.push(SnapshotEntry {
+                node_id: entry.node_id,
+                account_item_code,
+                target_month: entry.target_month,
+                entry_category: entry.entry_category,
+                amount: entry.amount,
+                description: entry.description,
+                histories: histories_by_entry.remove(&entry.id).unwrap_or_default(),
+            });
+        }
+
+        Ok(ScenarioSnapshot {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            exported_at: Utc::now(),
+            scenario: SnapshotScenario {
+                name: scenario.name,
+                description: scenario.description,
+                start_date: scenario.start_date,
+                end_date: scenario.end_date,
+                kind: scenario.kind,
+            },
+            nodes: snapshot_nodes,
+            entries: snapshot_entries,
+        })
+    }
+
+    /// アーカイブから新しいIDでシナリオを作成する
+    /// lineage_idは引き継ぐので、取り込んだシナリオは元のシナリオと比較できる
+    /// 参照の解決に失敗した場合は何も書き込まない
+    pub async fn import(
+        &self,
+        snapshot: ScenarioSnapshot,
+        name: Option<String>,
+        user_id: Uuid,
+    ) -> anyhow::Result<Scenario> {
+        if snapshot.format_version != SNAPSHOT_FORMAT_VERSION {
+            return Err(anyhow::anyhow!(
+                "Unsupported snapshot format version: {}",
+                snapshot.format_version
+            ));
+        }
+
+        let scenario = Scenario::new(
+            name.unwrap_or(snapshot.scenario.name),
+            snapshot.scenario.description,
+            snapshot.scenario.start_date,
+            snapshot.scenario.end_date,
+            snapshot.scenario.kind,
+            user_id,
+        )?;
+
+        let service_ids: HashMap<String, Uuid> = self
+            .service_repo
+            .find_all()
+            .await?
+            .into_iter()
+            .map(|s| (s.slug, s.id))
+            .collect();
+        let account_item_ids: HashMap<String, Uuid> = self
+            .account_item_repo
+            .find_all()
+            .await?
+            .into_iter()
+            .map(|item| (item.code, item.id))
+            .collect();
+
+        // アーカイブ内のノードID → 新しいノード
+        let mut id_map: HashMap<Uuid, PlanNode> = HashMap::new();
+        let mut new_nodes: Vec<PlanNode> = Vec::new();
+
+        for node in snapshot.nodes {
+            let parent = match node.parent_id {
+                Some(parent_ref) => Some(id_map.get(&parent_ref).ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Parent of node '{}' must appear before it in the snapshot",
+                        node.title
+                    )
+                })?),
+                None => None,
+            };
+
+            let valid_position = match parent {
+                Some(parent) => node.node_type.can_be_child_of(&parent.node_type),
+                None => node.node_type.can_be_root(),
+            };
+            if !valid_position {
+                return Err(anyhow::anyhow!(
+                    "Invalid node hierarchy in snapshot at node '{}'",
+                    node.title
+                ));
+            }
+
+            let service_id = match &node.service_slug {
+                Some(slug) => Some(*service_ids.get(slug).ok_or_else(|| {
+                    anyhow::anyhow!("Unknown service slug in snapshot: {}", slug)
+                })?),
+                None => None,
+            };
+
+            let new_node = PlanNode {
+                id: Uuid::new_v4(),
+                scenario_id: scenario.id,
+                parent_id: parent.map(|p| p.id),
+                lineage_id: node.lineage_id,
+                title: node.title,
+                description: node.description,
+                node_type: node.node_type,
+                display_order: node.display_order,
+                service_id,
+                // 担当者はアーカイブに含めないので、取り込んだ後に設定し直す
+                owner_id: None,
+                editor_ids: Vec::new(),
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                created_by: user_id,
+                updated_by: user_id,
+                deleted_at: None,
+                deleted_by: None,
+            };
+
+            if id_map.insert(node.id, new_node.clone()).is_some() {
+                return Err(anyhow::anyhow!(
+                    "Duplicate node id in snapshot: {}",
+                    node.id
+                ));
+            }
+            new_nodes.push(new_node);
+        }
+
+        // 履歴の変更者はemailで解決し、見つからない場合は取り込んだユーザーにする
+        let mut users: HashMap<String, Uuid> = HashMap::new();
+        let mut new_entries: Vec<PlEntry> = Vec::new();
+        let mut new_histories: Vec<PlEntryHistory> = Vec::new();
+
+        for entry in snapshot.entries {
+            let node = id_map.get(&entry.node_id).ok_or_else(|| {
+                anyhow::anyhow!("Unknown node id in snapshot entry: {}", entry.node_id)
+            })?;
+            let account_item_id =
+                *account_item_ids
+                    .get(&entry.account_item_code)
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Unknown account item code in snapshot: {}",
+                            entry.account_item_code
+                        )
+                    })?;
+
+            scenario
+                .validate_target_month(entry.target_month)
+                .map_err(|e| anyhow::anyhow!("Invalid entry in snapshot: {}", e))?;
+
+            let new_entry = PlEntry::new(
+                entry.target_month,
+                entry.entry_category,
+                node.id,
+                account_item_id,
+                entry.amount,
+                entry.description,
+                user_id,
+            );
+
+            for history in entry.histories {
+                let changed_by = match history.changed_by {
+                    Some(email) => match users.get(&email) {
+                        Some(id) => *id,
+                        None => {
+                            let id = self
+                                .user_repo
+                                .find_by_email(&email)
+                                .await?
+                                .map_or(user_id, |u| u.id);
+                            users.insert(email, id);
+                            id
+                        }
+                    },
+                    None => user_id,
+                };
+
+                new_histories.push(PlEntryHistory {
+                    id: Uuid::new_v4(),
+                    entry_id: new_entry.id,
+                    change_type: history.change_type,
+                    previous_amount: history.previous_amount,
+                    new_amount: history.new_amount,
+                    changed_at: history.changed_at,
+                    changed_by,
+                    operation_source: history.operation_source,
+                });
+            }
+
+            new_entries.push(new_entry);
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        let created = self.scenario_repo.create(&mut tx, &scenario).await?;
+        self.node_repo.create_many(&mut tx, new_nodes).await?;
+        self.entry_repo.create_many(&mut tx, new_entries).await?;
+        self.history_repo
+            .create_many(&mut tx, new_histories)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(created)
+    }
+}
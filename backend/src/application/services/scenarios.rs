@@ -1,27 +1,50 @@
-use crate::domain::pl_entries::{PlEntry, PlEntryRepository};
-use crate::domain::plan_nodes::{PlanNode, PlanNodeRepository};
-use crate::domain::scenarios::{Scenario, ScenarioRepository};
-use chrono::{NaiveDate, Utc};
-use std::collections::HashMap;
+use crate::domain::account_items::{AccountItemRepository, AccountType};
+use crate::domain::pl_entries::PlEntryRepository;
+use crate::domain::plan_nodes::PlanNodeRepository;
+use crate::domain::rollover::{self, RolloverOptions, RolloverPlan, RolloverSummary};
+use crate::domain::scenarios::{
+    Scenario, ScenarioKind, ScenarioRepository, ScenarioStatus, ScenarioStatusTransition,
+    ScenarioWithTransitions,
+};
+use crate::domain::services::ServiceRepository;
+use crate::domain::user::UserRole;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
-pub struct ScenarioService<S, N, E> {
+pub struct ScenarioService<S, N, E, V, A> {
+    pool: PgPool,
     scenario_repo: S,
     node_repo: N,
     entry_repo: E,
+    service_repo: V,
+    account_item_repo: A,
 }
 
-impl<S, N, E> ScenarioService<S, N, E>
+impl<S, N, E, V, A> ScenarioService<S, N, E, V, A>
 where
     S: ScenarioRepository,
     N: PlanNodeRepository,
     E: PlEntryRepository,
+    V: ServiceRepository,
+    A: AccountItemRepository,
 {
-    pub fn new(scenario_repo: S, node_repo: N, entry_repo: E) -> Self {
+    pub fn new(
+        pool: PgPool,
+        scenario_repo: S,
+        node_repo: N,
+        entry_repo: E,
+        service_repo: V,
+        account_item_repo: A,
+    ) -> Self {
         Self {
+            pool,
             scenario_repo,
             node_repo,
             entry_repo,
+            service_repo,
+            account_item_repo,
         }
     }
 
@@ -31,114 +54,308 @@ where
         description: Option<String>,
         start_date: NaiveDate,
         end_date: NaiveDate,
+        kind: ScenarioKind,
         user_id: Uuid,
     ) -> anyhow::Result<Scenario> {
-        let scenario = Scenario::new(name, description, start_date, end_date, user_id)?;
+        let scenario = Scenario::new(name, description, start_date, end_date, kind, user_id)?;
 
-        let created = self.scenario_repo.create(&scenario).await?;
+        let mut tx = self.pool.begin().await?;
+        let created = self.scenario_repo.create(&mut tx, &scenario).await?;
+        tx.commit().await?;
 
         Ok(created)
     }
 
-    pub async fn list_all(&self) -> anyhow::Result<Vec<Scenario>> {
-        self.scenario_repo.find_all().await
+    pub async fn list_all(&self) -> anyhow::Result<Vec<ScenarioWithTransitions>> {
+        let scenarios = self.scenario_repo.find_all().await?;
+
+        let mut transitions: HashMap<Uuid, Vec<ScenarioStatusTransition>> = HashMap::new();
+        for transition in self.scenario_repo.find_status_transitions().await? {
+            transitions
+                .entry(transition.scenario_id)
+                .or_default()
+                .push(transition);
+        }
+
+        Ok(scenarios
+            .into_iter()
+            .map(|scenario| ScenarioWithTransitions {
+                status_transitions: transitions.remove(&scenario.id).unwrap_or_default(),
+                scenario,
+            })
+            .collect())
+    }
+
+    /// 承認フローの状態を変更する (承認したシナリオは締め済みになる)
+    pub async fn transition_status(
+        &self,
+        id: Uuid,
+        to: ScenarioStatus,
+        comment: Option<String>,
+        user_id: Uuid,
+        role: &UserRole,
+    ) -> anyhow::Result<Scenario> {
+        let scenario = self
+            .scenario_repo
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Scenario not found"))?;
+
+        scenario
+            .status
+            .ensure_transition(to, role, comment.as_deref())?;
+
+        let transition = ScenarioStatusTransition::new(id, scenario.status, to, comment, user_id);
+        self.scenario_repo.transition_status(&transition).await
     }
 
     pub async fn activate(&self, id: Uuid) -> anyhow::Result<()> {
-        self.scenario_repo.set_current(id).await
+        let scenario = self
+            .scenario_repo
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Scenario not found"))?;
+        // 分岐シナリオは分岐元に取り込んで反映する
+        if scenario.parent_scenario_id.is_some() {
+            return Err(anyhow::anyhow!(
+                "Branch scenarios cannot be activated: merge them into their parent instead"
+            ));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        self.scenario_repo.set_current(&mut tx, id).await?;
+        tx.commit().await?;
+
+        Ok(())
     }
 
-    pub async fn rollover(
+    /// 作成中のシナリオを変えずに試算するため、ツリーとEntryをそのままコピーした分岐シナリオを作る
+    /// 分岐シナリオは作成中でなくても作成者が編集できる
+    pub async fn branch(
         &self,
         source_scenario_id: Uuid,
-        new_name: String,
-        new_start_date: NaiveDate,
-        new_end_date: NaiveDate,
+        name: String,
+        description: Option<String>,
+        kind: ScenarioKind,
         user_id: Uuid,
+        role: &UserRole,
     ) -> anyhow::Result<Scenario> {
         let source_scenario = self
             .scenario_repo
             .find_by_id(source_scenario_id)
             .await?
             .ok_or_else(|| anyhow::anyhow!("Source scenario not found"))?;
+        if !source_scenario.can_branch(user_id, role) {
+            return Err(anyhow::anyhow!(
+                "Permission denied: Only the current scenario or your own branch can be branched"
+            ));
+        }
 
-        let new_scenario = self
-            .create(
-                new_name,
-                Some(format!("Rollover from {}", source_scenario.name)),
-                new_start_date,
-                new_end_date,
-                user_id,
-            )
-            .await?;
+        let new_scenario = Scenario::branch(&source_scenario, name, description, kind, user_id)?;
 
-        let old_nodes = self
+        let nodes = self
             .node_repo
             .find_by_scenario_id(source_scenario_id)
             .await?;
+        let node_ids: Vec<Uuid> = nodes.iter().map(|n| n.id).collect();
+        let entries = self.entry_repo.find_by_node_ids(node_ids).await?;
+
+        // 期間が同じなので、月をずらさずに全てのノード・Entryをコピーする
+        let plan = rollover::plan(
+            &source_scenario,
+            &new_scenario,
+            &nodes,
+            &entries,
+            &RolloverOptions::default(),
+            &HashSet::new(),
+            &HashMap::new(),
+            user_id,
+        );
+
+        let mut tx = self.pool.begin().await?;
+
+        let created = self.scenario_repo.create(&mut tx, &new_scenario).await?;
+        self.node_repo.create_many(&mut tx, plan.nodes).await?;
+        self.entry_repo.create_many(&mut tx, plan.entries).await?;
+
+        tx.commit().await?;
+
+        Ok(created)
+    }
+
+    /// 締めたシナリオはノード・Entryの変更を一切受け付けない
+    pub async fn lock(&self, id: Uuid, user_id: Uuid) -> anyhow::Result<Scenario> {
+        self.scenario_repo.set_locked(id, true, user_id).await
+    }
+
+    /// 承認済みのシナリオは締めを解除すると作成中に戻る (遷移履歴に記録する)
+    pub async fn unlock(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        role: &UserRole,
+    ) -> anyhow::Result<Scenario> {
+        let scenario = self
+            .scenario_repo
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Scenario not found"))?;
 
-        let mut id_map: HashMap<Uuid, Uuid> = HashMap::new();
-        let mut new_nodes: Vec<PlanNode> = Vec::new();
+        if scenario.status == ScenarioStatus::Approved {
+            return self
+                .transition_status(
+                    id,
+                    ScenarioStatus::Draft,
+                    Some("Unlocked".to_string()),
+                    user_id,
+                    role,
+                )
+                .await;
+        }
+
+        self.scenario_repo.set_locked(id, false, user_id).await
+    }
+
+    // 新しいシナリオと、そこにコピーするノード・Entryを組み立てる (保存はしない)
+    #[allow(clippy::too_many_arguments)]
+    async fn prepare_rollover(
+        &self,
+        source_scenario_id: Uuid,
+        new_name: String,
+        new_start_date: NaiveDate,
+        new_end_date: NaiveDate,
+        options: &RolloverOptions,
+        user_id: Uuid,
+    ) -> anyhow::Result<(Scenario, RolloverPlan, HashMap<Uuid, AccountType>)> {
+        options.validate()?;
 
-        for old_node in &old_nodes {
-            let new_id = Uuid::new_v4();
-            id_map.insert(old_node.id, new_id);
+        let source_scenario = self
+            .scenario_repo
+            .find_by_id(source_scenario_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Source scenario not found"))?;
+        // 分岐シナリオを繰り越すと分岐元のない分岐シナリオができてしまう
+        if source_scenario.parent_scenario_id.is_some() {
+            return Err(anyhow::anyhow!(
+                "Branch scenarios cannot be rolled over: merge them into their parent first"
+            ));
         }
 
-        for old_node in &old_nodes {
-            let new_id = *id_map.get(&old_node.id).unwrap();
-
-            let new_parent_id = old_node.parent_id.and_then(|pid| id_map.get(&pid).copied());
-            let new_node = PlanNode {
-                id: new_id,
-                scenario_id: new_scenario.id,
-                parent_id: new_parent_id,
-                lineage_id: old_node.lineage_id,
-                title: old_node.title.clone(),
-                description: old_node.description.clone(),
-                node_type: old_node.node_type.clone(),
-                display_order: old_node.display_order,
-                service_id: old_node.service_id,
-                created_at: Utc::now(),
-                updated_at: Utc::now(),
-                created_by: user_id,
-                updated_by: user_id,
-                deleted_at: None,
-                deleted_by: None,
-            };
-
-            new_nodes.push(new_node);
+        let new_scenario = Scenario::new(
+            new_name,
+            Some(format!("Rollover from {}", source_scenario.name)),
+            new_start_date,
+            new_end_date,
+            source_scenario.kind,
+            user_id,
+        )?;
+
+        // 対象月をずらさずに期間が重ならないシナリオへ繰り越すと、Entryがすべて落ちてしまう
+        if !options.shift_months
+            && (new_scenario.start_date > source_scenario.end_date
+                || new_scenario.end_date < source_scenario.start_date)
+        {
+            return Err(anyhow::anyhow!(
+                "Scenario periods do not overlap: set shift_months to true to move entries into the new period"
+            ));
         }
 
-        self.node_repo.create_many(new_nodes).await?;
+        let old_nodes = self
+            .node_repo
+            .find_by_scenario_id(source_scenario_id)
+            .await?;
 
         let old_node_ids: Vec<Uuid> = old_nodes.iter().map(|n| n.id).collect();
         let old_entries = self.entry_repo.find_by_node_ids(old_node_ids).await?;
 
-        let mut new_entries: Vec<PlEntry> = Vec::new();
-        for old_entry in old_entries {
-            if let Some(&new_node_id) = id_map.get(&old_entry.node_id) {
-                let new_entry = PlEntry {
-                    id: Uuid::new_v4(),
-                    target_month: old_entry.target_month,
-                    entry_category: old_entry.entry_category.clone(),
-                    node_id: new_node_id,
-                    account_item_id: old_entry.account_item_id,
-                    amount: old_entry.amount,
-                    description: old_entry.description.clone(),
-                    created_at: Utc::now(),
-                    updated_at: Utc::now(),
-                    created_by: user_id,
-                    updated_by: user_id,
-                };
-                new_entries.push(new_entry);
-            }
-        }
+        let active_service_ids: HashSet<Uuid> = self
+            .service_repo
+            .find_all()
+            .await?
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+        let account_types: HashMap<Uuid, AccountType> = self
+            .account_item_repo
+            .find_all()
+            .await?
+            .into_iter()
+            .map(|item| (item.id, item.account_type))
+            .collect();
+
+        let plan = rollover::plan(
+            &source_scenario,
+            &new_scenario,
+            &old_nodes,
+            &old_entries,
+            options,
+            &active_service_ids,
+            &account_types,
+            user_id,
+        );
+
+        Ok((new_scenario, plan, account_types))
+    }
+
+    /// 繰越を書き込まずに、作成されるノード・Entryの件数と合計を返す
+    #[allow(clippy::too_many_arguments)]
+    pub async fn preview_rollover(
+        &self,
+        source_scenario_id: Uuid,
+        new_name: String,
+        new_start_date: NaiveDate,
+        new_end_date: NaiveDate,
+        options: RolloverOptions,
+        user_id: Uuid,
+    ) -> anyhow::Result<RolloverSummary> {
+        let (_, plan, account_types) = self
+            .prepare_rollover(
+                source_scenario_id,
+                new_name,
+                new_start_date,
+                new_end_date,
+                &options,
+                user_id,
+            )
+            .await?;
+
+        Ok(plan.summary(true, &account_types))
+    }
+
+    /// 旧シナリオのツリーとEntryを新しいシナリオにコピーし、作成中にする
+    /// 途中で失敗した場合に中途半端なシナリオが残らないよう、1つのトランザクションで行う
+    #[allow(clippy::too_many_arguments)]
+    pub async fn rollover(
+        &self,
+        source_scenario_id: Uuid,
+        new_name: String,
+        new_start_date: NaiveDate,
+        new_end_date: NaiveDate,
+        options: RolloverOptions,
+        user_id: Uuid,
+    ) -> anyhow::Result<Scenario> {
+        let (new_scenario, plan, _) = self
+            .prepare_rollover(
+                source_scenario_id,
+                new_name,
+                new_start_date,
+                new_end_date,
+                &options,
+                user_id,
+            )
+            .await?;
+
+        // トランザクション開始
+        let mut tx = self.pool.begin().await?;
 
-        self.entry_repo.create_many(new_entries).await?;
+        let created = self.scenario_repo.create(&mut tx, &new_scenario).await?;
+        self.node_repo.create_many(&mut tx, plan.nodes).await?;
+        self.entry_repo.create_many(&mut tx, plan.entries).await?;
+        self.scenario_repo.set_current(&mut tx, created.id).await?;
 
-        self.activate(new_scenario.id).await?;
+        // コミット
+        tx.commit().await?;
 
-        Ok(new_scenario)
+        Ok(created)
     }
 }
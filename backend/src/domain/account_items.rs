@@ -2,7 +2,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "account_type")]
 pub enum AccountType {
     Revenue,
@@ -10,6 +10,14 @@ pub enum AccountType {
     SellingGeneralAdmin,
 }
 
+impl AccountType {
+    /// 売上系の科目かどうか
+    /// 売上は多いほど好ましく、原価・販管費は少ないほど好ましい
+    pub fn is_revenue(&self) -> bool {
+        matches!(self, AccountType::Revenue)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct AccountItem {
     pub id: Uuid,
@@ -0,0 +1,133 @@
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::domain::scenarios::Scenario;
+
+/// 締めた月への確定値の書き込みを拒否したときのエラー
+#[derive(Debug, thiserror::Error)]
+#[error("Month {} is closed: Result entries cannot be edited", target_month.format("%Y-%m"))]
+pub struct MonthClosedError {
+    pub target_month: NaiveDate,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "closing_action")]
+pub enum ClosingAction {
+    Close,
+    Reopen,
+}
+
+/// 確定値を締めた月
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ClosedMonth {
+    pub scenario_id: Uuid,
+    pub target_month: NaiveDate, // YYYY-MM-01
+    pub closed_at: DateTime<Utc>,
+    pub closed_by: Uuid,
+}
+
+/// 締め・締め解除の対象月がシナリオ期間内の月初かを検証する
+pub fn validate_target_month(scenario: &Scenario, target_month: NaiveDate) -> anyhow::Result<()> {
+    if target_month.day() != 1 {
+        return Err(anyhow::anyhow!(
+            "Target month must be the first day of a month"
+        ));
+    }
+    if target_month < scenario.start_date || target_month > scenario.end_date {
+        return Err(anyhow::anyhow!(
+            "Target month must be within the scenario period"
+        ));
+    }
+
+    Ok(())
+}
+
+impl ClosedMonth {
+    pub fn new(
+        scenario: &Scenario,
+        target_month: NaiveDate,
+        user_id: Uuid,
+    ) -> anyhow::Result<Self> {
+        validate_target_month(scenario, target_month)?;
+
+        Ok(Self {
+            scenario_id: scenario.id,
+            target_month,
+            closed_at: Utc::now(),
+            closed_by: user_id,
+        })
+    }
+}
+
+/// 締め・締め解除の監査ログ
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ClosingEvent {
+    pub id: Uuid,
+    pub scenario_id: Uuid,
+    pub target_month: NaiveDate,
+    pub action: ClosingAction,
+    pub reason: Option<String>,
+    pub acted_at: DateTime<Utc>,
+    pub acted_by: Uuid,
+}
+
+impl ClosingEvent {
+    pub fn new(
+        scenario_id: Uuid,
+        target_month: NaiveDate,
+        action: ClosingAction,
+        reason: Option<String>,
+        user_id: Uuid,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            scenario_id,
+            target_month,
+            action,
+            reason,
+            acted_at: Utc::now(),
+            acted_by: user_id,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+pub trait ClosedMonthRepository: Send + Sync {
+    async fn find_by_scenario_id(&self, scenario_id: Uuid) -> anyhow::Result<Vec<ClosedMonth>>;
+    // ノードが属するシナリオでその月が締められているか
+    async fn is_closed_for_node(
+        &self,
+        tx: &mut PgConnection,
+        node_id: Uuid,
+        target_month: NaiveDate,
+    ) -> anyhow::Result<bool>;
+    // 締めと監査ログの記録を同じトランザクションで行う
+    async fn close(&self, month: &ClosedMonth, event: &ClosingEvent) -> anyhow::Result<()>;
+    async fn reopen(&self, event: &ClosingEvent) -> anyhow::Result<()>;
+    async fn find_events(&self, scenario_id: Uuid) -> anyhow::Result<Vec<ClosingEvent>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::test_fixtures::{month, scenario};
+
+    #[test]
+    fn validate_target_month_requires_a_month_start_within_the_period() {
+        let scenario = scenario(
+            month(2026, 4),
+            NaiveDate::from_ymd_opt(2027, 3, 31).unwrap(),
+        );
+
+        assert!(validate_target_month(&scenario, month(2026, 4)).is_ok());
+        assert!(validate_target_month(&scenario, month(2027, 3)).is_ok());
+        assert!(
+            validate_target_month(&scenario, NaiveDate::from_ymd_opt(2026, 5, 15).unwrap())
+                .is_err()
+        );
+        assert!(validate_target_month(&scenario, month(2026, 3)).is_err());
+        assert!(validate_target_month(&scenario, month(2027, 4)).is_err());
+    }
+}
@@ -0,0 +1,218 @@
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 集計期間の単位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PeriodGranularity {
+    Month,
+    Quarter,
+    Half,
+    Year,
+}
+
+impl PeriodGranularity {
+    fn months(&self) -> u32 {
+        match self {
+            PeriodGranularity::Month => 1,
+            PeriodGranularity::Quarter => 3,
+            PeriodGranularity::Half => 6,
+            PeriodGranularity::Year => 12,
+        }
+    }
+}
+
+/// 会計期間 (例: FY2026 Q1 = 2026-04-01 ~ 2026-06-01)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FiscalPeriod {
+    pub label: String,
+    pub start_month: NaiveDate, // 期間の最初の月 (YYYY-MM-01)
+    pub end_month: NaiveDate,   // 期間の最後の月 (YYYY-MM-01)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct FiscalCalendar {
+    pub fiscal_year_start_month: i32, // 期首月 (1-12)
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Option<Uuid>,
+}
+
+impl FiscalCalendar {
+    pub fn new(fiscal_year_start_month: i32, user_id: Uuid) -> anyhow::Result<Self> {
+        if !(1..=12).contains(&fiscal_year_start_month) {
+            return Err(anyhow::anyhow!(
+                "Fiscal year start month must be between 1 and 12"
+            ));
+        }
+
+        Ok(Self {
+            fiscal_year_start_month,
+            updated_at: Utc::now(),
+            updated_by: Some(user_id),
+        })
+    }
+
+    fn start_month(&self) -> u32 {
+        self.fiscal_year_start_month as u32
+    }
+
+    /// 日付が属する会計年度 (期首月の暦年で表す)
+    pub fn fiscal_year(&self, date: NaiveDate) -> i32 {
+        if date.month() >= self.start_month() {
+            date.year()
+        } else {
+            date.year() - 1
+        }
+    }
+
+    /// 期首月から数えて何ヶ月目か (0始まり)
+    fn month_offset(&self, date: NaiveDate) -> u32 {
+        (date.month() + 12 - self.start_month()) % 12
+    }
+
+    /// 日付が属する会計期間を返す
+    pub fn period_of(&self, date: NaiveDate, granularity: PeriodGranularity) -> FiscalPeriod {
+        let fiscal_year = self.fiscal_year(date);
+        let size = granularity.months();
+        let index = self.month_offset(date) / size;
+
+        let year_start = NaiveDate::from_ymd_opt(fiscal_year, self.start_month(), 1)
+            .expect("valid fiscal year start");
+        let start_month = year_start + Months::new(index * size);
+        let end_month = start_month + Months::new(size - 1);
+
+        let label = match granularity {
+            PeriodGranularity::Month => start_month.format("%Y-%m").to_string(),
+            PeriodGranularity::Quarter => format!("FY{} Q{}", fiscal_year, index + 1),
+            PeriodGranularity::Half => format!("FY{} H{}", fiscal_year, index + 1),
+            PeriodGranularity::Year => format!("FY{}", fiscal_year),
+        };
+
+        FiscalPeriod {
+            label,
+            start_month,
+            end_month,
+        }
+    }
+
+    /// シナリオの期間が会計四半期の区切りに沿っているかを検証する
+    /// start_dateは四半期の初日、end_dateは四半期の末日でなければならない
+    pub fn validate_scenario_period(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> anyhow::Result<()> {
+        let quarter_start = self
+            .period_of(start_date, PeriodGranularity::Quarter)
+            .start_month;
+        if start_date != quarter_start {
+            return Err(anyhow::anyhow!(
+                "Scenario start date must be the first day of a fiscal quarter (fiscal year starts in month {})",
+                self.fiscal_year_start_month
+            ));
+        }
+
+        let quarter_end = self
+            .period_of(end_date, PeriodGranularity::Quarter)
+            .end_month
+            + Months::new(1)
+            - chrono::Days::new(1);
+        if end_date != quarter_end {
+            return Err(anyhow::anyhow!(
+                "Scenario end date must be the last day of a fiscal quarter (fiscal year starts in month {})",
+                self.fiscal_year_start_month
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+pub trait FiscalCalendarRepository: Send + Sync {
+    async fn find(&self) -> anyhow::Result<FiscalCalendar>;
+    async fn update(&self, calendar: &FiscalCalendar) -> anyhow::Result<FiscalCalendar>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::test_fixtures::month;
+
+    fn calendar(fiscal_year_start_month: i32) -> FiscalCalendar {
+        FiscalCalendar::new(fiscal_year_start_month, Uuid::nil()).unwrap()
+    }
+
+    fn day(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn new_rejects_out_of_range_start_month() {
+        assert!(FiscalCalendar::new(0, Uuid::nil()).is_err());
+        assert!(FiscalCalendar::new(13, Uuid::nil()).is_err());
+    }
+
+    #[test]
+    fn period_of_buckets_months_from_the_fiscal_year_start() {
+        let april = calendar(4);
+
+        let q4 = april.period_of(day(2027, 2, 15), PeriodGranularity::Quarter);
+        assert_eq!(q4.label, "FY2026 Q4");
+        assert_eq!(q4.start_month, month(2027, 1));
+        assert_eq!(q4.end_month, month(2027, 3));
+
+        let h1 = april.period_of(month(2026, 9), PeriodGranularity::Half);
+        assert_eq!(h1.label, "FY2026 H1");
+        assert_eq!(h1.end_month, month(2026, 9));
+
+        let year = april.period_of(month(2026, 3), PeriodGranularity::Year);
+        assert_eq!(year.label, "FY2025");
+        assert_eq!(year.start_month, month(2025, 4));
+        assert_eq!(year.end_month, month(2026, 3));
+
+        let single = april.period_of(day(2026, 7, 31), PeriodGranularity::Month);
+        assert_eq!(single.label, "2026-07");
+        assert_eq!(single.start_month, single.end_month);
+
+        // 期首が1月なら暦年と一致する
+        let january = calendar(1);
+        assert_eq!(
+            january
+                .period_of(month(2026, 12), PeriodGranularity::Quarter)
+                .label,
+            "FY2026 Q4"
+        );
+    }
+
+    #[test]
+    fn validate_scenario_period_requires_quarter_boundaries() {
+        let april = calendar(4);
+
+        assert!(
+            april
+                .validate_scenario_period(day(2026, 4, 1), day(2027, 3, 31))
+                .is_ok()
+        );
+        assert!(
+            april
+                .validate_scenario_period(day(2026, 7, 1), day(2026, 9, 30))
+                .is_ok()
+        );
+        assert!(
+            april
+                .validate_scenario_period(day(2026, 5, 1), day(2026, 9, 30))
+                .is_err()
+        );
+        assert!(
+            april
+                .validate_scenario_period(day(2026, 4, 1), day(2026, 9, 29))
+                .is_err()
+        );
+        assert!(
+            april
+                .validate_scenario_period(day(2026, 4, 1), day(2026, 8, 31))
+                .is_err()
+        );
+    }
+}
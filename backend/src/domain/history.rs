@@ -56,4 +56,10 @@ impl PlEntryHistory {
 #[async_trait::async_trait]
 pub trait PlEntryHistoryRepository: Send + Sync {
     async fn create(&self, tx: &mut PgConnection, history: &PlEntryHistory) -> anyhow::Result<()>;
+    async fn find_by_entry_ids(&self, entry_ids: Vec<Uuid>) -> anyhow::Result<Vec<PlEntryHistory>>;
+    async fn create_many(
+        &self,
+        tx: &mut PgConnection,
+        histories: Vec<PlEntryHistory>,
+    ) -> anyhow::Result<()>;
 }
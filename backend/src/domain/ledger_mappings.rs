@@ -0,0 +1,141 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::account_items::AccountType;
+
+/// 会計システムの勘定科目コードと科目の対応
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct LedgerAccountMapping {
+    pub id: Uuid,
+    pub external_code: String,
+    pub account_item_id: Option<Uuid>, // NULLの場合は取り込み対象外
+
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl LedgerAccountMapping {
+    pub fn new(external_code: String, account_item_id: Option<Uuid>) -> anyhow::Result<Self> {
+        if external_code.trim().is_empty() {
+            return Err(anyhow::anyhow!("External code cannot be empty"));
+        }
+
+        Ok(Self {
+            id: Uuid::new_v4(),
+            external_code: external_code.trim().to_string(),
+            account_item_id,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        })
+    }
+}
+
+/// 会計システムの部門・タグとノード (またはサービス) の対応
+/// ノードはシナリオをまたいで同じ対応を使えるようにlineage_idで持つ
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct LedgerDepartmentMapping {
+    pub id: Uuid,
+    pub external_code: String,
+    pub lineage_id: Option<Uuid>,
+    pub service_id: Option<Uuid>,
+
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl LedgerDepartmentMapping {
+    pub fn new(
+        external_code: String,
+        lineage_id: Option<Uuid>,
+        service_id: Option<Uuid>,
+    ) -> anyhow::Result<Self> {
+        if external_code.trim().is_empty() {
+            return Err(anyhow::anyhow!("External code cannot be empty"));
+        }
+
+        if lineage_id.is_some() == service_id.is_some() {
+            return Err(anyhow::anyhow!(
+                "Exactly one of lineage_id or service_id must be set"
+            ));
+        }
+
+        Ok(Self {
+            id: Uuid::new_v4(),
+            external_code: external_code.trim().to_string(),
+            lineage_id,
+            service_id,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        })
+    }
+}
+
+/// 借方・貸方の金額をEntryの金額に変換する
+/// 収益は貸方、費用は借方をプラスとする (科目が不明な場合は費用として扱う)
+pub fn signed_amount(
+    account_type: Option<&AccountType>,
+    debit: Decimal,
+    credit: Decimal,
+) -> Decimal {
+    if account_type.is_some_and(|t| t.is_revenue()) {
+        credit - debit
+    } else {
+        debit - credit
+    }
+}
+
+#[async_trait::async_trait]
+pub trait LedgerMappingRepository: Send + Sync {
+    async fn find_account_mappings(&self) -> anyhow::Result<Vec<LedgerAccountMapping>>;
+    // external_codeが既に存在する場合は対応先を更新する
+    async fn upsert_account_mapping(
+        &self,
+        mapping: &LedgerAccountMapping,
+    ) -> anyhow::Result<LedgerAccountMapping>;
+    async fn delete_account_mapping(&self, id: Uuid) -> anyhow::Result<()>;
+
+    async fn find_department_mappings(&self) -> anyhow::Result<Vec<LedgerDepartmentMapping>>;
+    async fn upsert_department_mapping(
+        &self,
+        mapping: &LedgerDepartmentMapping,
+    ) -> anyhow::Result<LedgerDepartmentMapping>;
+    async fn delete_department_mapping(&self, id: Uuid) -> anyhow::Result<()>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signed_amount_counts_credits_as_revenue_and_debits_as_cost() {
+        let (debit, credit) = (Decimal::from(30), Decimal::from(100));
+
+        assert_eq!(
+            signed_amount(Some(&AccountType::Revenue), debit, credit),
+            Decimal::from(70)
+        );
+        assert_eq!(
+            signed_amount(Some(&AccountType::CostOfGoodsSold), debit, credit),
+            Decimal::from(-70)
+        );
+        assert_eq!(
+            signed_amount(Some(&AccountType::SellingGeneralAdmin), credit, debit),
+            Decimal::from(70)
+        );
+        assert_eq!(signed_amount(None, credit, debit), Decimal::from(70));
+    }
+
+    #[test]
+    fn department_mapping_requires_exactly_one_target() {
+        let code = "D01".to_string();
+
+        assert!(LedgerDepartmentMapping::new(code.clone(), None, None).is_err());
+        assert!(
+            LedgerDepartmentMapping::new(code.clone(), Some(Uuid::nil()), Some(Uuid::nil()))
+                .is_err()
+        );
+        assert!(LedgerDepartmentMapping::new(code, Some(Uuid::nil()), None).is_ok());
+    }
+}
@@ -1,7 +1,17 @@
 pub mod account_items;
+pub mod closed_months;
+pub mod fiscal_calendar;
 pub mod history;
+pub mod ledger_mappings;
 pub mod pl_entries;
+pub mod pl_reports;
 pub mod plan_nodes;
+pub mod rollover;
+pub mod scenario_merges;
+pub mod scenario_snapshots;
 pub mod scenarios;
 pub mod services;
+pub mod subtree_copies;
+#[cfg(test)]
+pub mod test_fixtures;
 pub mod user;
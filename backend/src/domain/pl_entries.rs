@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use sqlx::PgConnection;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "entry_category")]
 pub enum EntryCategory {
     Plan,
@@ -54,6 +54,22 @@ impl PlEntry {
     }
 }
 
+/// 対象月が月初日でない、またはシナリオ期間外の既存Entry (修復レポート用)
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct InvalidMonthEntry {
+    pub entry_id: Uuid,
+    pub scenario_id: Uuid,
+    pub scenario_name: String,
+    pub node_id: Uuid,
+    pub node_title: String,
+    pub account_item_id: Uuid,
+    pub target_month: NaiveDate,
+    pub entry_category: EntryCategory,
+    pub amount: Decimal,
+    pub not_month_start: bool,
+    pub outside_period: bool,
+}
+
 #[async_trait::async_trait]
 pub trait PlEntryRepository: Send + Sync {
     async fn find_by_cell(
@@ -78,5 +94,7 @@ pub trait PlEntryRepository: Send + Sync {
 
     async fn find_by_node_ids(&self, node_ids: Vec<Uuid>) -> anyhow::Result<Vec<PlEntry>>;
     async fn find_by_scenario_id(&self, scenario_id: Uuid) -> anyhow::Result<Vec<PlEntry>>;
-    async fn create_many(&self, entries: Vec<PlEntry>) -> anyhow::Result<()>;
+    async fn find_invalid_months(&self) -> anyhow::Result<Vec<InvalidMonthEntry>>;
+    async fn create_many(&self, tx: &mut PgConnection, entries: Vec<PlEntry>)
+    -> anyhow::Result<()>;
 }
@@ -0,0 +1,1183 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::AddAssign;
+use uuid::Uuid;
+
+use crate::domain::{
+    account_items::{AccountItem, AccountType},
+    pl_entries::{EntryCategory, PlEntry},
+    plan_nodes::{self, NodeType, PlanNode},
+    scenarios::Scenario,
+    services::Service,
+};
+
+/// 計画値と確定値の組
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CategoryAmounts {
+    pub plan: Decimal,
+    pub result: Decimal,
+}
+
+impl CategoryAmounts {
+    pub fn add(&mut self, category: &EntryCategory, amount: Decimal) {
+        match category {
+            EntryCategory::Plan => self.plan += amount,
+            EntryCategory::Result => self.result += amount,
+        }
+    }
+
+    pub fn get(&self, category: &EntryCategory) -> Decimal {
+        match category {
+            EntryCategory::Plan => self.plan,
+            EntryCategory::Result => self.result,
+        }
+    }
+}
+
+/// 科目・月ごとの集計セル
+#[derive(Debug, Clone, Serialize)]
+pub struct RollupCell {
+    pub account_item_id: Uuid,
+    pub target_month: NaiveDate,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period: Option<String>, // 会計期間で集計した場合のラベル (例: FY2026 Q1)
+    pub plan: Decimal,
+    pub result: Decimal,
+}
+
+/// ノードごとの集計結果
+/// 箱タイプのノードは子孫ノードのEntryを合計した値を持つ
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeRollup {
+    pub node_id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub lineage_id: Uuid,
+    pub title: String,
+    pub node_type: NodeType,
+    pub cells: Vec<RollupCell>,
+}
+
+/// ノードIDから親ノードIDを引くためのマップを作成する
+pub fn parent_map(nodes: &[PlanNode]) -> HashMap<Uuid, Option<Uuid>> {
+    nodes.iter().map(|n| (n.id, n.parent_id)).collect()
+}
+
+/// 自身を含む祖先ノードのIDを根に向かって列挙する
+/// ツリーに含まれないノードの場合は空になる
+pub fn ancestors_of(node_id: Uuid, parents: &HashMap<Uuid, Option<Uuid>>) -> Vec<Uuid> {
+    let mut ids = Vec::new();
+    let mut current = Some(node_id);
+
+    while let Some(id) = current {
+        // 不正なデータで循環していても無限ループしないようにする
+        if ids.len() > parents.len() {
+            break;
+        }
+        match parents.get(&id) {
+            Some(parent_id) => {
+                ids.push(id);
+                current = *parent_id;
+            }
+            None => break,
+        }
+    }
+
+    ids
+}
+
+/// シナリオのノードツリーを辿り、全ノードについて科目・月ごとの計画値/確定値を合計する
+/// 結果は引数のノード順で返す
+pub fn rollup(nodes: &[PlanNode], entries: &[PlEntry]) -> Vec<NodeRollup> {
+    let parents = parent_map(nodes);
+    let mut totals: HashMap<Uuid, BTreeMap<(NaiveDate, Uuid), CategoryAmounts>> = HashMap::new();
+
+    for entry in entries {
+        for id in ancestors_of(entry.node_id, &parents) {
+            totals
+                .entry(id)
+                .or_default()
+                .entry((entry.target_month, entry.account_item_id))
+                .or_default()
+                .add(&entry.entry_category, entry.amount);
+        }
+    }
+
+    nodes
+        .iter()
+        .map(|node| {
+            let cells = totals
+                .remove(&node.id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|((target_month, account_item_id), amounts)| RollupCell {
+                    account_item_id,
+                    target_month,
+                    period: None,
+                    plan: amounts.plan,
+                    result: amounts.result,
+                })
+                .collect();
+
+            NodeRollup {
+                node_id: node.id,
+                parent_id: node.parent_id,
+                lineage_id: node.lineage_id,
+                title: node.title.clone(),
+                node_type: node.node_type.clone(),
+                cells,
+            }
+        })
+        .collect()
+}
+
+/// 科目タイプごとの合計値
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AccountTypeTotals {
+    pub revenue: Decimal,
+    pub cost_of_goods_sold: Decimal,
+    pub selling_general_admin: Decimal,
+}
+
+impl AccountTypeTotals {
+    pub fn add(&mut self, account_type: &AccountType, amount: Decimal) {
+        match account_type {
+            AccountType::Revenue => self.revenue += amount,
+            AccountType::CostOfGoodsSold => self.cost_of_goods_sold += amount,
+            AccountType::SellingGeneralAdmin => self.selling_general_admin += amount,
+        }
+    }
+
+    /// 売上総利益・営業利益・利益率を計算する
+    pub fn statement(&self) -> PlStatementLines {
+        let gross_profit = self.revenue - self.cost_of_goods_sold;
+        let operating_profit = gross_profit - self.selling_general_admin;
+
+        PlStatementLines {
+            revenue: self.revenue,
+            cost_of_goods_sold: self.cost_of_goods_sold,
+            gross_profit,
+            selling_general_admin: self.selling_general_admin,
+            operating_profit,
+            gross_margin: margin(gross_profit, self.revenue),
+            operating_margin: margin(operating_profit, self.revenue),
+        }
+    }
+}
+
+impl AddAssign for AccountTypeTotals {
+    fn add_assign(&mut self, other: Self) {
+        self.revenue += other.revenue;
+        self.cost_of_goods_sold += other.cost_of_goods_sold;
+        self.selling_general_admin += other.selling_general_admin;
+    }
+}
+
+/// 売上に対する比率(%)を小数点以下2桁で返す
+/// 売上が0の場合は計算できないのでNone
+pub fn margin(amount: Decimal, revenue: Decimal) -> Option<Decimal> {
+    if revenue.is_zero() {
+        return None;
+    }
+    Some((amount / revenue * Decimal::ONE_HUNDRED).round_dp(2))
+}
+
+/// P/Lの計算行
+#[derive(Debug, Clone, Serialize)]
+pub struct PlStatementLines {
+    pub revenue: Decimal,
+    pub cost_of_goods_sold: Decimal,
+    pub gross_profit: Decimal,
+    pub selling_general_admin: Decimal,
+    pub operating_profit: Decimal,
+    pub gross_margin: Option<Decimal>,
+    pub operating_margin: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlStatementMonth {
+    pub target_month: NaiveDate,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period: Option<String>,
+    #[serde(flatten)]
+    pub lines: PlStatementLines,
+}
+
+/// 月別と期間合計のP/L
+#[derive(Debug, Clone, Serialize)]
+pub struct PlStatement {
+    pub months: Vec<PlStatementMonth>,
+    pub total: PlStatementLines,
+}
+
+/// Entryを科目タイプごとに集計してP/Lを作成する
+/// 科目が見つからないEntryは集計から除外する
+pub fn pl_statement<'a>(
+    entries: impl IntoIterator<Item = &'a PlEntry>,
+    account_types: &HashMap<Uuid, AccountType>,
+) -> PlStatement {
+    let mut monthly: BTreeMap<NaiveDate, AccountTypeTotals> = BTreeMap::new();
+    let mut total = AccountTypeTotals::default();
+
+    for entry in entries {
+        let Some(account_type) = account_types.get(&entry.account_item_id) else {
+            continue;
+        };
+        monthly
+            .entry(entry.target_month)
+            .or_default()
+            .add(account_type, entry.amount);
+        total.add(account_type, entry.amount);
+    }
+
+    PlStatement {
+        months: monthly
+            .into_iter()
+            .map(|(target_month, totals)| PlStatementMonth {
+                target_month,
+                period: None,
+                lines: totals.statement(),
+            })
+            .collect(),
+        total: total.statement(),
+    }
+}
+
+/// 計画値と確定値の差異
+#[derive(Debug, Clone, Serialize)]
+pub struct Variance {
+    pub plan: Decimal,
+    pub result: Decimal,
+    pub delta: Decimal,             // 確定値 - 計画値
+    pub delta_pct: Option<Decimal>, // 計画値に対する差異の比率(%)、計画値が0の場合はNone
+    pub favourable: bool,           // 計画に対して好ましい方向の差異かどうか
+}
+
+impl Variance {
+    /// higher_is_better: 確定値が計画値を上回った場合に好ましいかどうか
+    pub fn new(plan: Decimal, result: Decimal, higher_is_better: bool) -> Self {
+        let delta = result - plan;
+        let delta_pct = if plan.is_zero() {
+            None
+        } else {
+            Some((delta / plan.abs() * Decimal::ONE_HUNDRED).round_dp(2))
+        };
+        let favourable = if higher_is_better {
+            delta >= Decimal::ZERO
+        } else {
+            delta <= Decimal::ZERO
+        };
+
+        Self {
+            plan,
+            result,
+            delta,
+            delta_pct,
+            favourable,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VarianceCell {
+    pub account_item_id: Uuid,
+    pub target_month: NaiveDate,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period: Option<String>,
+    #[serde(flatten)]
+    pub variance: Variance,
+}
+
+/// ノードごとの予実差異
+/// operating_profitはノード配下の営業利益で比較した差異
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeVariance {
+    pub node_id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub title: String,
+    pub node_type: NodeType,
+    pub operating_profit: Variance,
+    pub cells: Vec<VarianceCell>,
+}
+
+/// 集計済みのノードごとに、セル単位とノード単位の予実差異を計算する
+/// 科目が見つからないセルは除外する
+pub fn variance(
+    rollups: Vec<NodeRollup>,
+    account_types: &HashMap<Uuid, AccountType>,
+) -> Vec<NodeVariance> {
+    rollups
+        .into_iter()
+        .map(|node| {
+            let mut plan_totals = AccountTypeTotals::default();
+            let mut result_totals = AccountTypeTotals::default();
+
+            let cells = node
+                .cells
+                .into_iter()
+                .filter_map(|cell| {
+                    let account_type = account_types.get(&cell.account_item_id)?;
+                    plan_totals.add(account_type, cell.plan);
+                    result_totals.add(account_type, cell.result);
+
+                    Some(VarianceCell {
+                        account_item_id: cell.account_item_id,
+                        target_month: cell.target_month,
+                        period: cell.period,
+                        variance: Variance::new(cell.plan, cell.result, account_type.is_revenue()),
+                    })
+                })
+                .collect();
+
+            NodeVariance {
+                node_id: node.node_id,
+                parent_id: node.parent_id,
+                title: node.title,
+                node_type: node.node_type,
+                operating_profit: Variance::new(
+                    plan_totals.statement().operating_profit,
+                    result_totals.statement().operating_profit,
+                    true,
+                ),
+                cells,
+            }
+        })
+        .collect()
+}
+
+/// シナリオ比較におけるノードの存在状況
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum LineagePresence {
+    Both,
+    BaseOnly,
+    TargetOnly,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ComparisonCell {
+    pub account_item_id: Uuid,
+    pub target_month: NaiveDate,
+    pub base: CategoryAmounts,
+    pub target: CategoryAmounts,
+    pub delta: CategoryAmounts, // target - base
+}
+
+/// lineage_idで対応付けたノードの比較結果
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeComparison {
+    pub lineage_id: Uuid,
+    pub base_node_id: Option<Uuid>,
+    pub target_node_id: Option<Uuid>,
+    pub title: String,
+    pub node_type: NodeType,
+    pub presence: LineagePresence,
+    pub cells: Vec<ComparisonCell>,
+}
+
+/// 2つのシナリオの集計結果をlineage_idで突き合わせ、ノード・科目・月ごとの差分を計算する
+/// targetのノード順に並べ、baseにしか存在しないノードは末尾に追加する
+pub fn compare(base: Vec<NodeRollup>, target: Vec<NodeRollup>) -> Vec<NodeComparison> {
+    let mut base_by_lineage: HashMap<Uuid, NodeRollup> =
+        base.iter().map(|n| (n.lineage_id, n.clone())).collect();
+
+    let mut comparisons: Vec<NodeComparison> = target
+        .into_iter()
+        .map(|target_node| {
+            let base_node = base_by_lineage.remove(&target_node.lineage_id);
+            compare_node(base_node, Some(target_node))
+        })
+        .collect();
+
+    // baseにしか存在しないノードは元の並び順のまま追加する
+    for base_node in base {
+        if let Some(node) = base_by_lineage.remove(&base_node.lineage_id) {
+            comparisons.push(compare_node(Some(node), None));
+        }
+    }
+
+    comparisons
+}
+
+fn compare_node(base: Option<NodeRollup>, target: Option<NodeRollup>) -> NodeComparison {
+    let presence = match (&base, &target) {
+        (Some(_), Some(_)) => LineagePresence::Both,
+        (Some(_), None) => LineagePresence::BaseOnly,
+        _ => LineagePresence::TargetOnly,
+    };
+    let head = target
+        .as_ref()
+        .or(base.as_ref())
+        .expect("either base or target node must exist");
+    let (lineage_id, title, node_type) =
+        (head.lineage_id, head.title.clone(), head.node_type.clone());
+    let base_node_id = base.as_ref().map(|n| n.node_id);
+    let target_node_id = target.as_ref().map(|n| n.node_id);
+
+    let mut cells: BTreeMap<(NaiveDate, Uuid), (CategoryAmounts, CategoryAmounts)> =
+        BTreeMap::new();
+    for cell in base.into_iter().flat_map(|n| n.cells) {
+        cells
+            .entry((cell.target_month, cell.account_item_id))
+            .or_default()
+            .0 = CategoryAmounts {
+            plan: cell.plan,
+            result: cell.result,
+        };
+    }
+    for cell in target.into_iter().flat_map(|n| n.cells) {
+        cells
+            .entry((cell.target_month, cell.account_item_id))
+            .or_default()
+            .1 = CategoryAmounts {
+            plan: cell.plan,
+            result: cell.result,
+        };
+    }
+
+    NodeComparison {
+        lineage_id,
+        base_node_id,
+        target_node_id,
+        title,
+        node_type,
+        presence,
+        cells: cells
+            .into_iter()
+            .map(
+                |((target_month, account_item_id), (base, target))| ComparisonCell {
+                    account_item_id,
+                    target_month,
+                    base,
+                    target,
+                    delta: CategoryAmounts {
+                        plan: target.plan - base.plan,
+                        result: target.result - base.result,
+                    },
+                },
+            )
+            .collect(),
+    }
+}
+
+/// 計画値と確定値それぞれの科目タイプ別合計
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlanResultTotals {
+    pub plan: AccountTypeTotals,
+    pub result: AccountTypeTotals,
+}
+
+impl PlanResultTotals {
+    pub fn add(&mut self, category: &EntryCategory, account_type: &AccountType, amount: Decimal) {
+        match category {
+            EntryCategory::Plan => self.plan.add(account_type, amount),
+            EntryCategory::Result => self.result.add(account_type, amount),
+        }
+    }
+
+    pub fn get(&self, category: &EntryCategory) -> AccountTypeTotals {
+        match category {
+            EntryCategory::Plan => self.plan,
+            EntryCategory::Result => self.result,
+        }
+    }
+
+    pub fn statement(&self) -> PlanResultStatement {
+        PlanResultStatement {
+            plan: self.plan.statement(),
+            result: self.result.statement(),
+        }
+    }
+}
+
+impl AddAssign for PlanResultTotals {
+    fn add_assign(&mut self, other: Self) {
+        self.plan += other.plan;
+        self.result += other.result;
+    }
+}
+
+/// 計画値と確定値のP/Lを並べたもの
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanResultStatement {
+    pub plan: PlStatementLines,
+    pub result: PlStatementLines,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ServicePlMonth {
+    pub target_month: NaiveDate,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period: Option<String>,
+    #[serde(flatten)]
+    pub statement: PlanResultStatement,
+}
+
+/// サービスごとのP/L
+#[derive(Debug, Clone, Serialize)]
+pub struct ServicePl {
+    pub service_id: Uuid,
+    pub service_name: String,
+    pub service_slug: String,
+    pub months: Vec<ServicePlMonth>,
+    pub total: PlanResultStatement,
+}
+
+/// ノードのservice_idを通してEntryをサービスごとに集計する
+/// 結果は引数のサービス順で返す
+pub fn service_pl(
+    services: &[Service],
+    nodes: &[PlanNode],
+    entries: &[PlEntry],
+    account_types: &HashMap<Uuid, AccountType>,
+) -> Vec<ServicePl> {
+    let node_services: HashMap<Uuid, Uuid> = nodes
+        .iter()
+        .filter_map(|n| n.service_id.map(|sid| (n.id, sid)))
+        .collect();
+
+    let mut totals: HashMap<Uuid, BTreeMap<NaiveDate, PlanResultTotals>> = HashMap::new();
+    for entry in entries {
+        let (Some(service_id), Some(account_type)) = (
+            node_services.get(&entry.node_id),
+            account_types.get(&entry.account_item_id),
+        ) else {
+            continue;
+        };
+        totals
+            .entry(*service_id)
+            .or_default()
+            .entry(entry.target_month)
+            .or_default()
+            .add(&entry.entry_category, account_type, entry.amount);
+    }
+
+    services
+        .iter()
+        .map(|service| {
+            let monthly = totals.remove(&service.id).unwrap_or_default();
+
+            let mut total = PlanResultTotals::default();
+            for month in monthly.values() {
+                total += *month;
+            }
+
+            ServicePl {
+                service_id: service.id,
+                service_name: service.name.clone(),
+                service_slug: service.slug.clone(),
+                months: monthly
+                    .into_iter()
+                    .map(|(target_month, totals)| ServicePlMonth {
+                        target_month,
+                        period: None,
+                        statement: totals.statement(),
+                    })
+                    .collect(),
+                total: total.statement(),
+            }
+        })
+        .collect()
+}
+
+/// 見込み値を計算するときに、対象月でどちらの値を採用するか
+/// 締め済みの月 (cutoffより前) は確定値、それ以降は計画値を使う
+pub fn forecast_source(target_month: NaiveDate, cutoff: NaiveDate) -> EntryCategory {
+    if target_month < cutoff {
+        EntryCategory::Result
+    } else {
+        EntryCategory::Plan
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ForecastCell {
+    pub node_id: Uuid,
+    pub account_item_id: Uuid,
+    pub target_month: NaiveDate,
+    pub source: EntryCategory, // forecastに採用した値
+    pub plan: Decimal,
+    pub result: Decimal,
+    pub forecast: Decimal,
+}
+
+/// 計画値・確定値・見込み値のP/L
+#[derive(Debug, Clone, Serialize)]
+pub struct ForecastStatement {
+    pub plan: PlStatementLines,
+    pub result: PlStatementLines,
+    pub forecast: PlStatementLines,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ForecastMonth {
+    pub target_month: NaiveDate,
+    pub source: EntryCategory,
+    #[serde(flatten)]
+    pub statement: ForecastStatement,
+}
+
+/// 着地見込み
+#[derive(Debug, Clone, Serialize)]
+pub struct Forecast {
+    pub cutoff: NaiveDate,
+    pub cells: Vec<ForecastCell>,
+    pub months: Vec<ForecastMonth>,
+    pub total: ForecastStatement,
+}
+
+/// cutoffより前の月は確定値、cutoff以降の月は計画値を採用して着地見込みを計算する
+/// セル (ノード・科目・月) 単位の値と、月別・期間合計のP/Lを返す
+pub fn forecast(
+    entries: &[PlEntry],
+    cutoff: NaiveDate,
+    account_types: &HashMap<Uuid, AccountType>,
+) -> Forecast {
+    let mut cells: BTreeMap<(Uuid, Uuid, NaiveDate), CategoryAmounts> = BTreeMap::new();
+    let mut monthly: BTreeMap<NaiveDate, PlanResultTotals> = BTreeMap::new();
+
+    for entry in entries {
+        cells
+            .entry((entry.node_id, entry.account_item_id, entry.target_month))
+            .or_default()
+            .add(&entry.entry_category, entry.amount);
+
+        if let Some(account_type) = account_types.get(&entry.account_item_id) {
+            monthly.entry(entry.target_month).or_default().add(
+                &entry.entry_category,
+                account_type,
+                entry.amount,
+            );
+        }
+    }
+
+    let mut total = PlanResultTotals::default();
+    let mut total_forecast = AccountTypeTotals::default();
+    let months = monthly
+        .into_iter()
+        .map(|(target_month, totals)| {
+            let source = forecast_source(target_month, cutoff);
+            let forecast = totals.get(&source);
+            total += totals;
+            total_forecast += forecast;
+
+            ForecastMonth {
+                target_month,
+                source,
+                statement: ForecastStatement {
+                    plan: totals.plan.statement(),
+                    result: totals.result.statement(),
+                    forecast: forecast.statement(),
+                },
+            }
+        })
+        .collect();
+
+    Forecast {
+        cutoff,
+        cells: cells
+            .into_iter()
+            .map(|((node_id, account_item_id, target_month), amounts)| {
+                let source = forecast_source(target_month, cutoff);
+                let forecast = amounts.get(&source);
+                ForecastCell {
+                    node_id,
+                    account_item_id,
+                    target_month,
+                    source,
+                    plan: amounts.plan,
+                    result: amounts.result,
+                    forecast,
+                }
+            })
+            .collect(),
+        months,
+        total: ForecastStatement {
+            plan: total.plan.statement(),
+            result: total.result.statement(),
+            forecast: total_forecast.statement(),
+        },
+    }
+}
+
+/// グリッドの1行 (ノード × 科目)
+#[derive(Debug, Clone, Serialize)]
+pub struct PlGridRow {
+    pub node_id: Uuid,
+    pub node_path: Vec<String>, // ルートからのノードタイトル
+    pub node_type: NodeType,
+    pub account_item_id: Uuid,
+    pub account_item_code: String,
+    pub account_item_name: String,
+    pub values: Vec<Option<Decimal>>, // monthsと同じ並び、未入力のセルはNone
+}
+
+/// スプレッドシート形式で編集するためのグリッド
+#[derive(Debug, Clone, Serialize)]
+pub struct PlGrid {
+    pub entry_category: EntryCategory,
+    pub months: Vec<NaiveDate>,
+    pub rows: Vec<PlGridRow>,
+}
+
+/// 実体ノード × 科目を行、月を列としたグリッドを作成する
+/// 行はツリーの深さ優先順・科目のdisplay_order順に並べる
+/// monthsに含まれない月のEntryは含めない
+pub fn pl_grid(
+    nodes: &[PlanNode],
+    account_items: &[AccountItem],
+    entries: &[PlEntry],
+    category: EntryCategory,
+    months: Vec<NaiveDate>,
+) -> PlGrid {
+    let columns: HashMap<NaiveDate, usize> =
+        months.iter().enumerate().map(|(i, m)| (*m, i)).collect();
+
+    let mut values: HashMap<(Uuid, Uuid), Vec<Option<Decimal>>> = HashMap::new();
+    for entry in entries.iter().filter(|e| e.entry_category == category) {
+        let Some(&column) = columns.get(&entry.target_month) else {
+            continue;
+        };
+        values
+            .entry((entry.node_id, entry.account_item_id))
+            .or_insert_with(|| vec![None; months.len()])[column] = Some(entry.amount);
+    }
+
+    let mut rows = Vec::new();
+    for (node, node_path) in plan_nodes::depth_first(nodes) {
+        if !node.node_type.is_entity() {
+            continue;
+        }
+        for item in account_items {
+            rows.push(PlGridRow {
+                node_id: node.id,
+                node_path: node_path.clone(),
+                node_type: node.node_type.clone(),
+                account_item_id: item.id,
+                account_item_code: item.code.clone(),
+                account_item_name: item.name.clone(),
+                values: values
+                    .remove(&(node.id, item.id))
+                    .unwrap_or_else(|| vec![None; months.len()]),
+            });
+        }
+    }
+
+    PlGrid {
+        entry_category: category,
+        months,
+        rows,
+    }
+}
+
+/// 出力用の列 (期間の最初の月と見出し)
+#[derive(Debug, Clone)]
+pub struct ReportColumn {
+    pub month: NaiveDate,
+    pub label: String,
+}
+
+/// シナリオをファイルとして出力するためのデータ
+#[derive(Debug, Clone)]
+pub struct ScenarioExport {
+    pub scenario: Scenario,
+    pub nodes: Vec<PlanNode>,
+    pub account_items: Vec<AccountItem>,
+    pub rollups: Vec<NodeRollup>,
+    pub columns: Vec<ReportColumn>,
+}
+
+/// 箱タイプのノードの小計として、集計値から月ごとの営業利益を計算する
+/// 科目が見つからないセルは除外する
+pub fn operating_profit_by_month(
+    rollup: &NodeRollup,
+    category: &EntryCategory,
+    account_types: &HashMap<Uuid, AccountType>,
+) -> BTreeMap<NaiveDate, Decimal> {
+    let mut monthly: BTreeMap<NaiveDate, AccountTypeTotals> = BTreeMap::new();
+    for cell in &rollup.cells {
+        let Some(account_type) = account_types.get(&cell.account_item_id) else {
+            continue;
+        };
+        let amount = match category {
+            EntryCategory::Plan => cell.plan,
+            EntryCategory::Result => cell.result,
+        };
+        monthly
+            .entry(cell.target_month)
+            .or_default()
+            .add(account_type, amount);
+    }
+
+    monthly
+        .into_iter()
+        .map(|(month, totals)| (month, totals.statement().operating_profit))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::services::Service;
+    use crate::domain::test_fixtures::{entry, month, node};
+
+    fn cells_of(rollups: &[NodeRollup], node_id: Uuid) -> &[RollupCell] {
+        &rollups.iter().find(|r| r.node_id == node_id).unwrap().cells
+    }
+
+    #[test]
+    fn rollup_totals_container_nodes_from_their_descendants() {
+        let revenue = Uuid::new_v4();
+        let ini = node("Ini", None, NodeType::Initiative);
+        let prj = node("Prj", Some(&ini), NodeType::Project);
+        let job1 = node("Job1", Some(&prj), NodeType::Job);
+        let job2 = node("Job2", Some(&prj), NodeType::Job);
+        let nodes = vec![ini.clone(), prj.clone(), job1.clone(), job2.clone()];
+        let entries = vec![
+            entry(&job1, revenue, month(2026, 4), EntryCategory::Plan, 100),
+            entry(&job2, revenue, month(2026, 4), EntryCategory::Plan, 50),
+            entry(&job2, revenue, month(2026, 4), EntryCategory::Result, 70),
+            entry(&job2, revenue, month(2026, 5), EntryCategory::Plan, 30),
+        ];
+
+        let rollups = rollup(&nodes, &entries);
+
+        let ordered: Vec<Uuid> = rollups.iter().map(|r| r.node_id).collect();
+        assert_eq!(ordered, vec![ini.id, prj.id, job1.id, job2.id]);
+
+        for id in [ini.id, prj.id] {
+            let cells = cells_of(&rollups, id);
+            assert_eq!(cells.len(), 2);
+            assert_eq!(cells[0].target_month, month(2026, 4));
+            assert_eq!(cells[0].plan, Decimal::from(150));
+            assert_eq!(cells[0].result, Decimal::from(70));
+            assert_eq!(cells[1].target_month, month(2026, 5));
+            assert_eq!(cells[1].plan, Decimal::from(30));
+        }
+
+        let cells = cells_of(&rollups, job1.id);
+        assert_eq!(cells.len(), 1);
+        assert_eq!(cells[0].plan, Decimal::from(100));
+        assert_eq!(cells[0].result, Decimal::ZERO);
+    }
+
+    #[test]
+    fn rollup_ignores_entries_of_nodes_outside_the_tree() {
+        let ini = node("Ini", None, NodeType::Initiative);
+        let other = node("Other", None, NodeType::Initiative);
+        let entries = vec![entry(
+            &other,
+            Uuid::new_v4(),
+            month(2026, 4),
+            EntryCategory::Plan,
+            100,
+        )];
+
+        let rollups = rollup(std::slice::from_ref(&ini), &entries);
+
+        assert_eq!(rollups.len(), 1);
+        assert!(rollups[0].cells.is_empty());
+    }
+
+    #[test]
+    fn ancestors_of_stops_on_cycles() {
+        let a = Uuid::new_v4();
+        let b = Uuid::new_v4();
+        let parents = HashMap::from([(a, Some(b)), (b, Some(a))]);
+
+        assert!(ancestors_of(a, &parents).len() <= parents.len() + 1);
+        assert!(ancestors_of(Uuid::new_v4(), &parents).is_empty());
+    }
+
+    #[test]
+    fn pl_statement_computes_profits_and_margins() {
+        let (revenue, cogs, sga) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let account_types = HashMap::from([
+            (revenue, AccountType::Revenue),
+            (cogs, AccountType::CostOfGoodsSold),
+            (sga, AccountType::SellingGeneralAdmin),
+        ]);
+        let root = node("Initiative", None, NodeType::Initiative);
+        let job = node("Job", Some(&root), NodeType::Job);
+        let entries = vec![
+            entry(&job, revenue, month(2026, 4), EntryCategory::Plan, 1000),
+            entry(&job, cogs, month(2026, 4), EntryCategory::Plan, 600),
+            entry(&job, sga, month(2026, 4), EntryCategory::Plan, 300),
+            entry(&job, sga, month(2026, 5), EntryCategory::Plan, 100),
+            // 科目が見つからないEntryは集計しない
+            entry(
+                &job,
+                Uuid::new_v4(),
+                month(2026, 4),
+                EntryCategory::Plan,
+                999,
+            ),
+        ];
+
+        let statement = pl_statement(&entries, &account_types);
+
+        assert_eq!(statement.months.len(), 2);
+        let april = &statement.months[0].lines;
+        assert_eq!(april.gross_profit, Decimal::from(400));
+        assert_eq!(april.operating_profit, Decimal::from(100));
+        assert_eq!(april.gross_margin, Some(Decimal::from(40)));
+        assert_eq!(april.operating_margin, Some(Decimal::from(10)));
+
+        // 売上がない月は利益率を計算しない
+        let may = &statement.months[1].lines;
+        assert_eq!(may.operating_profit, Decimal::from(-100));
+        assert_eq!(may.gross_margin, None);
+
+        assert_eq!(statement.total.revenue, Decimal::from(1000));
+        assert_eq!(statement.total.operating_profit, Decimal::ZERO);
+        assert_eq!(statement.total.operating_margin, Some(Decimal::ZERO));
+    }
+
+    #[test]
+    fn margin_rounds_to_two_decimal_places() {
+        assert_eq!(
+            margin(Decimal::from(1), Decimal::from(3)),
+            Some(Decimal::new(3333, 2))
+        );
+        assert_eq!(margin(Decimal::from(1), Decimal::ZERO), None);
+    }
+
+    #[test]
+    fn variance_flags_favourable_direction_by_account_type() {
+        let over = Variance::new(Decimal::from(200), Decimal::from(250), true);
+        assert_eq!(over.delta, Decimal::from(50));
+        assert_eq!(over.delta_pct, Some(Decimal::from(25)));
+        assert!(over.favourable);
+
+        // 費用は計画を上回ると好ましくない
+        let cost_over = Variance::new(Decimal::from(200), Decimal::from(250), false);
+        assert!(!cost_over.favourable);
+
+        // 計画値が負でも差異の向きで比率の符号が決まる
+        let negative_plan = Variance::new(Decimal::from(-100), Decimal::from(-50), true);
+        assert_eq!(negative_plan.delta_pct, Some(Decimal::from(50)));
+
+        let no_plan = Variance::new(Decimal::ZERO, Decimal::from(10), true);
+        assert_eq!(no_plan.delta_pct, None);
+    }
+
+    #[test]
+    fn variance_compares_operating_profit_per_node() {
+        let (revenue, sga) = (Uuid::new_v4(), Uuid::new_v4());
+        let account_types = HashMap::from([
+            (revenue, AccountType::Revenue),
+            (sga, AccountType::SellingGeneralAdmin),
+        ]);
+        let ini = node("Ini", None, NodeType::Initiative);
+        let job = node("Job", Some(&ini), NodeType::Job);
+        let entries = vec![
+            entry(&job, revenue, month(2026, 4), EntryCategory::Plan, 1000),
+            entry(&job, revenue, month(2026, 4), EntryCategory::Result, 900),
+            entry(&job, sga, month(2026, 4), EntryCategory::Plan, 400),
+            entry(&job, sga, month(2026, 4), EntryCategory::Result, 350),
+            entry(
+                &job,
+                Uuid::new_v4(),
+                month(2026, 4),
+                EntryCategory::Plan,
+                999,
+            ),
+        ];
+
+        let variances = variance(rollup(&[ini, job.clone()], &entries), &account_types);
+
+        let job_variance = variances.iter().find(|v| v.node_id == job.id).unwrap();
+        assert_eq!(job_variance.cells.len(), 2);
+        assert_eq!(job_variance.operating_profit.plan, Decimal::from(600));
+        assert_eq!(job_variance.operating_profit.result, Decimal::from(550));
+        assert!(!job_variance.operating_profit.favourable);
+
+        let sga_cell = job_variance
+            .cells
+            .iter()
+            .find(|c| c.account_item_id == sga)
+            .unwrap();
+        assert!(sga_cell.variance.favourable);
+    }
+
+    fn rollup_of(lineage_id: Uuid, title: &str, cells: Vec<RollupCell>) -> NodeRollup {
+        NodeRollup {
+            node_id: Uuid::new_v4(),
+            parent_id: None,
+            lineage_id,
+            title: title.to_string(),
+            node_type: NodeType::Job,
+            cells,
+        }
+    }
+
+    fn plan_cell(account_item_id: Uuid, target_month: NaiveDate, plan: i64) -> RollupCell {
+        RollupCell {
+            account_item_id,
+            target_month,
+            period: None,
+            plan: Decimal::from(plan),
+            result: Decimal::ZERO,
+        }
+    }
+
+    #[test]
+    fn compare_matches_nodes_on_lineage_id() {
+        let revenue = Uuid::new_v4();
+        let (kept, dropped, added) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let base = vec![
+            rollup_of(
+                dropped,
+                "Dropped",
+                vec![plan_cell(revenue, month(2026, 4), 30)],
+            ),
+            rollup_of(
+                kept,
+                "Old title",
+                vec![plan_cell(revenue, month(2026, 4), 100)],
+            ),
+        ];
+        let target = vec![
+            rollup_of(
+                kept,
+                "New title",
+                vec![plan_cell(revenue, month(2026, 5), 80)],
+            ),
+            rollup_of(added, "Added", vec![]),
+        ];
+
+        let comparisons = compare(base, target);
+
+        let lineages: Vec<Uuid> = comparisons.iter().map(|c| c.lineage_id).collect();
+        assert_eq!(lineages, vec![kept, added, dropped]);
+        let presences: Vec<LineagePresence> =
+            comparisons.iter().map(|c| c.presence.clone()).collect();
+        assert_eq!(
+            presences,
+            vec![
+                LineagePresence::Both,
+                LineagePresence::TargetOnly,
+                LineagePresence::BaseOnly
+            ]
+        );
+
+        // タイトルはtarget側を優先し、片側にしかないセルは0と比較する
+        let kept_comparison = &comparisons[0];
+        assert_eq!(kept_comparison.title, "New title");
+        assert_eq!(kept_comparison.cells.len(), 2);
+        assert_eq!(kept_comparison.cells[0].delta.plan, Decimal::from(-100));
+        assert_eq!(kept_comparison.cells[1].delta.plan, Decimal::from(80));
+        assert_eq!(comparisons[2].target_node_id, None);
+    }
+
+    #[test]
+    fn service_pl_aggregates_entries_through_node_services() {
+        let (revenue, cogs) = (Uuid::new_v4(), Uuid::new_v4());
+        let account_types = HashMap::from([
+            (revenue, AccountType::Revenue),
+            (cogs, AccountType::CostOfGoodsSold),
+        ]);
+        let ads = Service::new("Ads".to_string(), "ads".to_string(), 0).unwrap();
+        let idle = Service::new("Idle".to_string(), "idle".to_string(), 1).unwrap();
+        let ini = node("Ini", None, NodeType::Initiative);
+        let mut job1 = node("Job1", Some(&ini), NodeType::Job);
+        job1.service_id = Some(ads.id);
+        let mut job2 = node("Job2", Some(&ini), NodeType::Job);
+        job2.service_id = Some(ads.id);
+        let entries = vec![
+            entry(&job1, revenue, month(2026, 4), EntryCategory::Plan, 500),
+            entry(&job2, cogs, month(2026, 4), EntryCategory::Plan, 200),
+            entry(&job2, revenue, month(2026, 5), EntryCategory::Result, 300),
+        ];
+
+        let pls = service_pl(
+            &[ads.clone(), idle.clone()],
+            &[ini, job1, job2],
+            &entries,
+            &account_types,
+        );
+
+        assert_eq!(pls.len(), 2);
+        assert_eq!(pls[0].service_id, ads.id);
+        assert_eq!(pls[0].months.len(), 2);
+        assert_eq!(
+            pls[0].months[0].statement.plan.gross_profit,
+            Decimal::from(300)
+        );
+        assert_eq!(pls[0].total.plan.revenue, Decimal::from(500));
+        assert_eq!(pls[0].total.result.revenue, Decimal::from(300));
+
+        // Entryのないサービスも空のP/Lとして返す
+        assert_eq!(pls[1].service_id, idle.id);
+        assert!(pls[1].months.is_empty());
+        assert_eq!(pls[1].total.plan.revenue, Decimal::ZERO);
+    }
+
+    #[test]
+    fn forecast_uses_results_before_the_cutoff_and_plans_after() {
+        let revenue = Uuid::new_v4();
+        let account_types = HashMap::from([(revenue, AccountType::Revenue)]);
+        let ini = node("Ini", None, NodeType::Initiative);
+        let job = node("Job", Some(&ini), NodeType::Job);
+        let entries = vec![
+            entry(&job, revenue, month(2026, 4), EntryCategory::Plan, 100),
+            entry(&job, revenue, month(2026, 4), EntryCategory::Result, 90),
+            entry(&job, revenue, month(2026, 5), EntryCategory::Plan, 200),
+            entry(&job, revenue, month(2026, 5), EntryCategory::Result, 10),
+        ];
+
+        let forecast = forecast(&entries, month(2026, 5), &account_types);
+
+        assert_eq!(forecast.cells.len(), 2);
+        assert_eq!(forecast.cells[0].source, EntryCategory::Result);
+        assert_eq!(forecast.cells[0].forecast, Decimal::from(90));
+        // cutoffの月はまだ締まっていないので計画値を使う
+        assert_eq!(forecast.cells[1].source, EntryCategory::Plan);
+        assert_eq!(forecast.cells[1].forecast, Decimal::from(200));
+
+        assert_eq!(forecast.months.len(), 2);
+        assert_eq!(forecast.total.plan.revenue, Decimal::from(300));
+        assert_eq!(forecast.total.result.revenue, Decimal::from(100));
+        assert_eq!(forecast.total.forecast.revenue, Decimal::from(290));
+    }
+
+    #[test]
+    fn operating_profit_by_month_subtracts_costs_from_revenue() {
+        let revenue = Uuid::new_v4();
+        let cost = Uuid::new_v4();
+        let ini = node("Ini", None, NodeType::Initiative);
+        let prj = node("Prj", Some(&ini), NodeType::Project);
+        let job = node("Job", Some(&prj), NodeType::Job);
+        let entries = vec![
+            entry(&job, revenue, month(2026, 4), EntryCategory::Plan, 300),
+            entry(&job, cost, month(2026, 4), EntryCategory::Plan, 120),
+            entry(&job, cost, month(2026, 4), EntryCategory::Result, 90),
+            entry(&job, cost, month(2026, 5), EntryCategory::Plan, 40),
+            entry(
+                &job,
+                Uuid::new_v4(),
+                month(2026, 5),
+                EntryCategory::Plan,
+                999,
+            ),
+        ];
+        let account_types = HashMap::from([
+            (revenue, AccountType::Revenue),
+            (cost, AccountType::SellingGeneralAdmin),
+        ]);
+        let rollups = rollup(&[ini.clone(), prj, job], &entries);
+        let ini_rollup = rollups.iter().find(|r| r.node_id == ini.id).unwrap();
+
+        let plan = operating_profit_by_month(ini_rollup, &EntryCategory::Plan, &account_types);
+        assert_eq!(
+            plan,
+            BTreeMap::from([
+                (month(2026, 4), Decimal::from(180)),
+                (month(2026, 5), Decimal::from(-40)),
+            ])
+        );
+
+        let result = operating_profit_by_month(ini_rollup, &EntryCategory::Result, &account_types);
+        assert_eq!(result[&month(2026, 4)], Decimal::from(-90));
+    }
+}
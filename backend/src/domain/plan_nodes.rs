@@ -1,7 +1,12 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
+use crate::domain::scenarios::Scenario;
+use crate::domain::user::UserRole;
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "node_type")]
 pub enum NodeType {
@@ -14,6 +19,7 @@ pub enum NodeType {
 
 impl NodeType {
     /// Nodeの親子関係ルール
+    #[allow(clippy::match_like_matches_macro)]
     pub fn can_be_child_of(&self, parent_type: &NodeType) -> bool {
         match (parent_type, self) {
             (NodeType::Initiative, NodeType::Project) => true,
@@ -59,6 +65,11 @@ pub struct PlanNode {
     // NodeTypeが箱タイプの場合はNone
     pub service_id: Option<Uuid>,
 
+    // 担当者 (Memberは自身が担当するノードとその子孫のみ編集できる)
+    // Noneまたは空の場合は親ノードの担当者を引き継ぐ
+    pub owner_id: Option<Uuid>,
+    pub editor_ids: Vec<Uuid>,
+
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub created_by: Uuid,
@@ -71,6 +82,7 @@ pub struct PlanNode {
 }
 
 impl PlanNode {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         scenario_id: Uuid,
         parent_id: Option<Uuid>,
@@ -116,6 +128,8 @@ impl PlanNode {
             node_type,
             display_order,
             service_id,
+            owner_id: None,
+            editor_ids: Vec::new(),
             created_at: Utc::now(),
             updated_at: Utc::now(),
             created_by: user_id,
@@ -126,6 +140,223 @@ impl PlanNode {
     }
 }
 
+impl PlanNode {
+    /// user_idがこのノードの担当者 (owner・editor) か
+    pub fn is_assigned_to(&self, user_id: Uuid) -> bool {
+        self.owner_id == Some(user_id) || self.editor_ids.contains(&user_id)
+    }
+}
+
+/// user_idがnode_idのノード (Noneの場合はRoot) の下を編集できるか
+/// Manager・Adminは全ノード、Memberは自身または祖先が担当するノードのみ編集できる
+/// nodesにはnode_idのノードとその祖先が含まれている必要がある
+pub fn can_edit(nodes: &[PlanNode], node_id: Option<Uuid>, user_id: Uuid, role: &UserRole) -> bool {
+    if matches!(role, UserRole::Admin | UserRole::Manager) {
+        return true;
+    }
+
+    let by_id: HashMap<Uuid, &PlanNode> = nodes.iter().map(|n| (n.id, n)).collect();
+    let mut visited = HashSet::new();
+    let mut current = node_id;
+    // 不正なデータで循環していても無限ループしないようにする
+    while let Some(id) = current.filter(|id| visited.insert(*id)) {
+        let Some(node) = by_id.get(&id) else {
+            break;
+        };
+        if node.is_assigned_to(user_id) {
+            return true;
+        }
+        current = node.parent_id;
+    }
+
+    false
+}
+
+/// scenarioのnode_idのノードの下を編集できるか
+/// 作成中でない自分の分岐シナリオは担当に関わらず編集でき、取り込む際に担当を確認する
+pub fn can_edit_in(
+    scenario: &Scenario,
+    nodes: &[PlanNode],
+    node_id: Option<Uuid>,
+    user_id: Uuid,
+    role: &UserRole,
+) -> bool {
+    scenario.is_private_branch_of(user_id) || can_edit(nodes, node_id, user_id, role)
+}
+
+/// ツリーを深さ優先で辿った順にノードを並べ、ルートからのパス (タイトル) を添えて返す
+/// 兄弟ノードは引数の並び順 (display_order順) を保つ
+/// 親が含まれていないノードはルートとして扱う
+pub fn depth_first(nodes: &[PlanNode]) -> Vec<(&PlanNode, Vec<String>)> {
+    let ids: HashSet<Uuid> = nodes.iter().map(|n| n.id).collect();
+    let mut children: HashMap<Option<Uuid>, Vec<&PlanNode>> = HashMap::new();
+    for node in nodes {
+        let parent_id = node.parent_id.filter(|pid| ids.contains(pid));
+        children.entry(parent_id).or_default().push(node);
+    }
+
+    fn walk<'a>(
+        parent_id: Option<Uuid>,
+        path: &[String],
+        children: &HashMap<Option<Uuid>, Vec<&'a PlanNode>>,
+        visited: &mut HashSet<Uuid>,
+        out: &mut Vec<(&'a PlanNode, Vec<String>)>,
+    ) {
+        for node in children.get(&parent_id).into_iter().flatten() {
+            // 不正なデータで循環していても無限ループしないようにする
+            if !visited.insert(node.id) {
+                continue;
+            }
+            let mut node_path = path.to_vec();
+            node_path.push(node.title.clone());
+            out.push((node, node_path.clone()));
+            walk(Some(node.id), &node_path, children, visited, out);
+        }
+    }
+
+    let mut out = Vec::with_capacity(nodes.len());
+    walk(None, &[], &children, &mut HashSet::new(), &mut out);
+    out
+}
+
+/// 再帰クエリで取得したツリーの1行 (PlanNodeに深さ・パス・子ノード数を加えたもの)
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PlanNodeTreeRow {
+    pub id: Uuid,
+    pub scenario_id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub lineage_id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub node_type: NodeType,
+    pub display_order: i32,
+    pub service_id: Option<Uuid>,
+    pub owner_id: Option<Uuid>,
+    pub editor_ids: Vec<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_by: Uuid,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub deleted_by: Option<Uuid>,
+
+    pub depth: i32,        // 起点のノードを0とした深さ
+    pub path: Vec<String>, // Rootから親までのタイトル
+    pub child_count: i64,  // 深さの制限で省略した子ノードも含む
+}
+
+/// 入れ子のツリー
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanNodeTree {
+    #[serde(flatten)]
+    pub node: PlanNode,
+    pub depth: i32,
+    pub path: Vec<String>,
+    pub child_count: i64,
+    pub children: Vec<PlanNodeTree>,
+}
+
+/// 親が先に並んだ行から入れ子のツリーを組み立てる
+/// 兄弟ノードは行の並び順を保つ
+pub fn build_tree(rows: Vec<PlanNodeTreeRow>) -> Vec<PlanNodeTree> {
+    let ids: HashSet<Uuid> = rows.iter().map(|r| r.id).collect();
+    let mut children: HashMap<Option<Uuid>, Vec<PlanNodeTreeRow>> = HashMap::new();
+    for row in rows {
+        // 起点のノードは親が結果に含まれない
+        let parent_id = row.parent_id.filter(|pid| ids.contains(pid));
+        children.entry(parent_id).or_default().push(row);
+    }
+
+    fn attach(
+        parent_id: Option<Uuid>,
+        children: &mut HashMap<Option<Uuid>, Vec<PlanNodeTreeRow>>,
+    ) -> Vec<PlanNodeTree> {
+        let rows = children.remove(&parent_id).unwrap_or_default();
+        rows.into_iter()
+            .map(|row| {
+                let id = row.id;
+                PlanNodeTree {
+                    depth: row.depth,
+                    path: row.path,
+                    child_count: row.child_count,
+                    node: PlanNode {
+                        id: row.id,
+                        scenario_id: row.scenario_id,
+                        parent_id: row.parent_id,
+                        lineage_id: row.lineage_id,
+                        title: row.title,
+                        description: row.description,
+                        node_type: row.node_type,
+                        display_order: row.display_order,
+                        service_id: row.service_id,
+                        owner_id: row.owner_id,
+                        editor_ids: row.editor_ids,
+                        created_at: row.created_at,
+                        updated_at: row.updated_at,
+                        created_by: row.created_by,
+                        updated_by: row.updated_by,
+                        deleted_at: row.deleted_at,
+                        deleted_by: row.deleted_by,
+                    },
+                    children: attach(Some(id), children),
+                }
+            })
+            .collect()
+    }
+
+    attach(None, &mut children)
+}
+
+/// root_idのノードと、その子孫のID
+pub fn subtree_ids(nodes: &[PlanNode], root_id: Uuid) -> HashSet<Uuid> {
+    let mut children: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for node in nodes {
+        if let Some(pid) = node.parent_id {
+            children.entry(pid).or_default().push(node.id);
+        }
+    }
+
+    let mut ids = HashSet::new();
+    let mut stack = vec![root_id];
+    while let Some(id) = stack.pop() {
+        // 不正なデータで循環していても無限ループしないようにする
+        if ids.insert(id) {
+            stack.extend(children.get(&id).into_iter().flatten());
+        }
+    }
+    ids
+}
+
+/// root_idの削除済みノードと、同じ操作でまとめて削除された子孫のID
+/// 先に個別に削除されていた子孫は含めない
+pub fn deleted_together(deleted: &[PlanNode], root_id: Uuid) -> HashSet<Uuid> {
+    let Some(deleted_at) = deleted
+        .iter()
+        .find(|n| n.id == root_id)
+        .and_then(|n| n.deleted_at)
+    else {
+        return HashSet::new();
+    };
+
+    let batch: Vec<PlanNode> = deleted
+        .iter()
+        .filter(|n| n.deleted_at == Some(deleted_at))
+        .cloned()
+        .collect();
+    subtree_ids(&batch, root_id)
+}
+
+/// ゴミ箱の1件 (まとめて削除したノードの起点)
+#[derive(Debug, Clone, Serialize)]
+pub struct TrashedPlanNode {
+    #[serde(flatten)]
+    pub node: PlanNode,
+    pub deleted_at: DateTime<Utc>,
+    pub deleted_by: Option<Uuid>,
+    pub node_count: usize,  // 一緒に削除された子孫を含むノード数
+    pub entry_count: usize, // 復元すると再び集計に含まれるEntryの件数
+}
+
 pub struct UpdatePlanNodeParams {
     pub title: Option<String>,
     pub description: Option<String>,
@@ -135,15 +366,246 @@ pub struct UpdatePlanNodeParams {
 #[async_trait::async_trait]
 pub trait PlanNodeRepository: Send + Sync {
     async fn create(&self, node: &PlanNode) -> anyhow::Result<PlanNode>;
-    async fn create_many(&self, nodes: Vec<PlanNode>) -> anyhow::Result<()>;
+    async fn create_many(&self, tx: &mut PgConnection, nodes: Vec<PlanNode>) -> anyhow::Result<()>;
     async fn find_recent(&self, limit: i64) -> anyhow::Result<Vec<PlanNode>>;
     async fn find_by_id(&self, id: Uuid) -> anyhow::Result<Option<PlanNode>>;
     async fn find_by_scenario_id(&self, scenario_id: Uuid) -> anyhow::Result<Vec<PlanNode>>;
+    // root_idを省略した場合はシナリオ全体 (Rootから)、max_depthを省略した場合は末端まで
+    async fn find_tree(
+        &self,
+        scenario_id: Uuid,
+        root_id: Option<Uuid>,
+        max_depth: Option<i32>,
+    ) -> anyhow::Result<Vec<PlanNodeTreeRow>>;
+    // ノードとその祖先 (Rootまで) を取得する
+    async fn find_with_ancestors(&self, id: Uuid) -> anyhow::Result<Vec<PlanNode>>;
+    // 付け替え中にツリーが変更されないよう、シナリオのノードを行ロックして取得する
+    async fn find_by_scenario_id_for_update(
+        &self,
+        tx: &mut PgConnection,
+        scenario_id: Uuid,
+    ) -> anyhow::Result<Vec<PlanNode>>;
     async fn update(
         &self,
+        tx: &mut PgConnection,
         id: Uuid,
         node: UpdatePlanNodeParams,
         updated_by: Uuid,
     ) -> anyhow::Result<PlanNode>;
-    async fn delete(&self, id: Uuid) -> anyhow::Result<()>;
+    async fn update_assignees(
+        &self,
+        id: Uuid,
+        owner_id: Option<Uuid>,
+        editor_ids: Vec<Uuid>,
+        updated_by: Uuid,
+    ) -> anyhow::Result<PlanNode>;
+    async fn move_to(
+        &self,
+        tx: &mut PgConnection,
+        id: Uuid,
+        parent_id: Option<Uuid>,
+        display_order: i32,
+        updated_by: Uuid,
+    ) -> anyhow::Result<PlanNode>;
+    // 論理削除 (まとめて削除したノードは同じdeleted_atになる)
+    // Entryは残し、ノードと一緒に集計から外れる
+    async fn delete(
+        &self,
+        tx: &mut PgConnection,
+        ids: Vec<Uuid>,
+        deleted_by: Uuid,
+    ) -> anyhow::Result<()>;
+    async fn find_deleted_by_id(&self, id: Uuid) -> anyhow::Result<Option<PlanNode>>;
+    async fn find_deleted_by_scenario_id(&self, scenario_id: Uuid)
+    -> anyhow::Result<Vec<PlanNode>>;
+    // 復元した件数を返す
+    async fn restore(
+        &self,
+        tx: &mut PgConnection,
+        ids: Vec<Uuid>,
+        restored_by: Uuid,
+    ) -> anyhow::Result<u64>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::scenarios::ScenarioKind;
+    use crate::domain::test_fixtures::{month, node, scenario};
+    use chrono::Duration;
+
+    #[test]
+    fn subtree_ids_contains_descendants_only() {
+        let ini = node("Ini", None, NodeType::Initiative);
+        let prj = node("Prj", Some(&ini), NodeType::Project);
+        let sub = node("Sub", Some(&prj), NodeType::SubProject);
+        let job = node("Job", Some(&sub), NodeType::Job);
+        let other = node("Other", Some(&ini), NodeType::Project);
+        let nodes = vec![
+            ini.clone(),
+            prj.clone(),
+            sub.clone(),
+            job.clone(),
+            other.clone(),
+        ];
+
+        let ids = subtree_ids(&nodes, prj.id);
+        assert_eq!(ids, HashSet::from([prj.id, sub.id, job.id]));
+        // 自身の子孫の下への移動は循環するので、移動先が含まれることで検出する
+        assert!(ids.contains(&job.id));
+        assert!(!ids.contains(&other.id));
+    }
+
+    fn tree_row(node: &PlanNode, depth: i32) -> PlanNodeTreeRow {
+        PlanNodeTreeRow {
+            id: node.id,
+            scenario_id: node.scenario_id,
+            parent_id: node.parent_id,
+            lineage_id: node.lineage_id,
+            title: node.title.clone(),
+            description: None,
+            node_type: node.node_type.clone(),
+            display_order: node.display_order,
+            service_id: node.service_id,
+            owner_id: node.owner_id,
+            editor_ids: node.editor_ids.clone(),
+            created_at: node.created_at,
+            updated_at: node.updated_at,
+            created_by: node.created_by,
+            updated_by: node.updated_by,
+            deleted_at: None,
+            deleted_by: None,
+            depth,
+            path: Vec::new(),
+            child_count: 0,
+        }
+    }
+
+    #[test]
+    fn build_tree_nests_rows_under_the_start_node() {
+        let ini = node("Ini", None, NodeType::Initiative);
+        let prj = node("Prj", Some(&ini), NodeType::Project);
+        let job1 = node("Job1", Some(&prj), NodeType::Job);
+        let job2 = node("Job2", Some(&prj), NodeType::Job);
+
+        // 起点のPrjの親 (Ini) は結果に含まれない
+        let tree = build_tree(vec![
+            tree_row(&prj, 0),
+            tree_row(&job1, 1),
+            tree_row(&job2, 1),
+        ]);
+
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree[0].node.id, prj.id);
+        let children: Vec<Uuid> = tree[0].children.iter().map(|c| c.node.id).collect();
+        assert_eq!(children, vec![job1.id, job2.id]);
+        assert!(tree[0].children.iter().all(|c| c.children.is_empty()));
+    }
+
+    #[test]
+    fn deleted_together_skips_descendants_deleted_earlier() {
+        let now = Utc::now();
+        let ini = node("Ini", None, NodeType::Initiative);
+        let mut prj = node("Prj", Some(&ini), NodeType::Project);
+        let mut job1 = node("Job1", Some(&prj), NodeType::Job);
+        let mut job2 = node("Job2", Some(&prj), NodeType::Job);
+        prj.deleted_at = Some(now);
+        job1.deleted_at = Some(now);
+        job2.deleted_at = Some(now - Duration::minutes(5));
+        let deleted = vec![prj.clone(), job1.clone(), job2];
+
+        assert_eq!(
+            deleted_together(&deleted, prj.id),
+            HashSet::from([prj.id, job1.id])
+        );
+        assert!(deleted_together(&deleted, Uuid::new_v4()).is_empty());
+    }
+
+    #[test]
+    fn can_edit_inherits_assignment_from_ancestors() {
+        let member = Uuid::new_v4();
+        let mut ini = node("Ini", None, NodeType::Initiative);
+        ini.owner_id = Some(member);
+        let prj = node("Prj", Some(&ini), NodeType::Project);
+        let job = node("Job", Some(&prj), NodeType::Job);
+        let nodes = vec![ini.clone(), prj, job.clone()];
+
+        assert!(can_edit(&nodes, Some(job.id), member, &UserRole::Member));
+        assert!(can_edit(&nodes, Some(ini.id), member, &UserRole::Member));
+    }
+
+    #[test]
+    fn can_edit_rejects_members_not_assigned() {
+        let member = Uuid::new_v4();
+        let ini = node("Ini", None, NodeType::Initiative);
+        let mut prj = node("Prj", Some(&ini), NodeType::Project);
+        prj.editor_ids = vec![member];
+        let other = node("Other", Some(&ini), NodeType::Project);
+        let nodes = vec![ini.clone(), prj, other.clone()];
+
+        // 子孫の担当者は祖先・兄弟を編集できない
+        assert!(!can_edit(&nodes, Some(ini.id), member, &UserRole::Member));
+        assert!(!can_edit(&nodes, Some(other.id), member, &UserRole::Member));
+        // Root直下への作成は担当者がいないので編集できない
+        assert!(!can_edit(&nodes, None, member, &UserRole::Member));
+    }
+
+    #[test]
+    fn can_edit_allows_managers_and_admins_everywhere() {
+        let user = Uuid::new_v4();
+        let ini = node("Ini", None, NodeType::Initiative);
+        let nodes = vec![ini.clone()];
+
+        for role in [UserRole::Admin, UserRole::Manager] {
+            assert!(can_edit(&nodes, Some(ini.id), user, &role));
+            assert!(can_edit(&nodes, None, user, &role));
+        }
+    }
+
+    #[test]
+    fn can_edit_stops_on_cycles() {
+        let member = Uuid::new_v4();
+        let ini = node("Ini", None, NodeType::Initiative);
+        let mut a = node("A", Some(&ini), NodeType::Project);
+        let mut b = node("B", Some(&a), NodeType::Project);
+        a.parent_id = Some(b.id);
+        b.parent_id = Some(a.id);
+
+        assert!(!can_edit(
+            &[a.clone(), b],
+            Some(a.id),
+            member,
+            &UserRole::Member
+        ));
+    }
+
+    #[test]
+    fn can_edit_in_skips_assignment_only_in_the_members_own_non_current_branch() {
+        let member = Uuid::new_v4();
+        let base = scenario(month(2026, 4), month(2027, 3));
+        let branch_of = |user_id: Uuid| {
+            Scenario::branch(
+                &base,
+                "What-if".to_string(),
+                None,
+                ScenarioKind::WhatIf,
+                user_id,
+            )
+            .unwrap()
+        };
+        let ini = node("Ini", None, NodeType::Initiative);
+        let nodes = vec![ini.clone()];
+        let can_edit_ini = |scenario: &Scenario| {
+            can_edit_in(scenario, &nodes, Some(ini.id), member, &UserRole::Member)
+        };
+
+        assert!(can_edit_ini(&branch_of(member)));
+        // 作成中になった分岐シナリオでは担当外のノードを編集できない
+        let mut current_branch = branch_of(member);
+        current_branch.is_current = true;
+        assert!(!can_edit_ini(&current_branch));
+        // 他のユーザーの分岐シナリオ、分岐シナリオでないシナリオも同様
+        assert!(!can_edit_ini(&branch_of(Uuid::new_v4())));
+        assert!(!can_edit_ini(&base));
+    }
 }
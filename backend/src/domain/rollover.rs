@@ -0,0 +1,449 @@
+use chrono::{Datelike, Months, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::domain::account_items::AccountType;
+use crate::domain::pl_entries::{EntryCategory, PlEntry};
+use crate::domain::pl_reports::{PlanResultStatement, PlanResultTotals};
+use crate::domain::plan_nodes::{self, PlanNode};
+use crate::domain::scenarios::Scenario;
+
+/// 繰越でコピーするEntryの種類
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RolloverEntryMode {
+    #[default]
+    All, // 計画値・確定値をそのままコピー
+    Plan,
+    Result,
+    ResultAsPlan, // 確定値を翌期の計画値としてコピー
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RolloverOptions {
+    // 旧シナリオと新シナリオのstart_dateの差だけ対象月をずらす
+    pub shift_months: bool,
+    pub entry_mode: RolloverEntryMode,
+    // 終了したサービス (削除済み) に紐づくノードをコピーしない
+    pub drop_closed: bool,
+    // 科目タイプごとの増減率 (%)
+    pub uplift: HashMap<AccountType, Decimal>,
+}
+
+impl RolloverOptions {
+    /// 増減率は-100% (ゼロにする) から1000%までとする
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let min = Decimal::from(-100);
+        let max = Decimal::from(1000);
+        for (account_type, rate) in &self.uplift {
+            if *rate < min || *rate > max {
+                return Err(anyhow::anyhow!(
+                    "Uplift for {:?} must be between -100 and 1000 percent",
+                    account_type
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum DropReason {
+    ClosedService, // 終了したサービスに紐づく
+    Orphan,        // 親ノードがコピーされない
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DroppedNode {
+    pub node_id: Uuid,
+    pub lineage_id: Uuid,
+    pub title: String,
+    pub reason: DropReason,
+}
+
+/// 繰越で作成するノード・Entryと、コピーしなかったもの
+#[derive(Debug, Clone)]
+pub struct RolloverPlan {
+    pub nodes: Vec<PlanNode>, // 親が子より先に並ぶ
+    pub entries: Vec<PlEntry>,
+    pub dropped_nodes: Vec<DroppedNode>,
+    pub dropped_entries: usize, // 新しい期間から外れた (または対象月が不正な) Entryの件数
+}
+
+fn month_offset(from: NaiveDate, to: NaiveDate) -> i32 {
+    (to.year() * 12 + to.month() as i32) - (from.year() * 12 + from.month() as i32)
+}
+
+pub fn shift_month(date: NaiveDate, offset: i32) -> Option<NaiveDate> {
+    if offset >= 0 {
+        date.checked_add_months(Months::new(offset as u32))
+    } else {
+        date.checked_sub_months(Months::new(offset.unsigned_abs()))
+    }
+}
+
+/// 旧シナリオのノード・Entryから新シナリオ用のコピーを作る
+/// active_service_idsは終了していないサービスのID
+#[allow(clippy::too_many_arguments)]
+pub fn plan(
+    source: &Scenario,
+    target: &Scenario,
+    nodes: &[PlanNode],
+    entries: &[PlEntry],
+    options: &RolloverOptions,
+    active_service_ids: &HashSet<Uuid>,
+    account_types: &HashMap<Uuid, AccountType>,
+    user_id: Uuid,
+) -> RolloverPlan {
+    let now = Utc::now();
+    let mut id_map: HashMap<Uuid, Uuid> = HashMap::new();
+    let mut new_nodes: Vec<PlanNode> = Vec::new();
+    let mut dropped_nodes: Vec<DroppedNode> = Vec::new();
+
+    // 深さ優先で辿り、親が落ちたノードは子孫ごと落とす
+    for (node, _) in plan_nodes::depth_first(nodes) {
+        let reason = match node.parent_id {
+            Some(pid) if !id_map.contains_key(&pid) => Some(DropReason::Orphan),
+            _ if options.drop_closed
+                && node
+                    .service_id
+                    .is_some_and(|sid| !active_service_ids.contains(&sid)) =>
+            {
+                Some(DropReason::ClosedService)
+            }
+            _ => None,
+        };
+
+        if let Some(reason) = reason {
+            dropped_nodes.push(DroppedNode {
+                node_id: node.id,
+                lineage_id: node.lineage_id,
+                title: node.title.clone(),
+                reason,
+            });
+            continue;
+        }
+
+        let new_id = Uuid::new_v4();
+        id_map.insert(node.id, new_id);
+
+        new_nodes.push(PlanNode {
+            id: new_id,
+            scenario_id: target.id,
+            parent_id: node.parent_id.and_then(|pid| id_map.get(&pid).copied()),
+            lineage_id: node.lineage_id,
+            title: node.title.clone(),
+            description: node.description.clone(),
+            node_type: node.node_type.clone(),
+            display_order: node.display_order,
+            service_id: node.service_id,
+            owner_id: node.owner_id,
+            editor_ids: node.editor_ids.clone(),
+            created_at: now,
+            updated_at: now,
+            created_by: user_id,
+            updated_by: user_id,
+            deleted_at: None,
+            deleted_by: None,
+        });
+    }
+
+    let offset = month_offset(source.start_date, target.start_date);
+    let mut new_entries: Vec<PlEntry> = Vec::new();
+    let mut dropped_entries = 0;
+
+    for entry in entries {
+        let Some(&new_node_id) = id_map.get(&entry.node_id) else {
+            continue;
+        };
+
+        let entry_category = match (options.entry_mode, &entry.entry_category) {
+            (RolloverEntryMode::All, category) => category.clone(),
+            (RolloverEntryMode::Plan, EntryCategory::Plan) => EntryCategory::Plan,
+            (RolloverEntryMode::Result, EntryCategory::Result) => EntryCategory::Result,
+            (RolloverEntryMode::ResultAsPlan, EntryCategory::Result) => EntryCategory::Plan,
+            _ => continue,
+        };
+
+        let target_month = if options.shift_months {
+            shift_month(entry.target_month, offset)
+        } else {
+            Some(entry.target_month)
+        };
+        // 新しいシナリオの期間外、または月初日でない月のEntryはコピーしない
+        let Some(target_month) =
+            target_month.filter(|month| target.validate_target_month(*month).is_ok())
+        else {
+            dropped_entries += 1;
+            continue;
+        };
+
+        let amount = match account_types
+            .get(&entry.account_item_id)
+            .and_then(|t| options.uplift.get(t))
+        {
+            Some(rate) => {
+                (entry.amount * (Decimal::ONE_HUNDRED + rate) / Decimal::ONE_HUNDRED).round_dp(4)
+            }
+            None => entry.amount,
+        };
+
+        new_entries.push(PlEntry {
+            id: Uuid::new_v4(),
+            target_month,
+            entry_category,
+            node_id: new_node_id,
+            account_item_id: entry.account_item_id,
+            amount,
+            description: entry.description.clone(),
+            created_at: now,
+            updated_at: now,
+            created_by: user_id,
+            updated_by: user_id,
+        });
+    }
+
+    RolloverPlan {
+        nodes: new_nodes,
+        entries: new_entries,
+        dropped_nodes,
+        dropped_entries,
+    }
+}
+
+/// 繰越の結果 (dry-runの場合は書き込まずに見込みを返す)
+#[derive(Debug, Clone, Serialize)]
+pub struct RolloverSummary {
+    pub dry_run: bool,
+    pub node_count: usize,
+    pub entry_count: usize,
+    pub totals: PlanResultStatement, // コピー後の科目タイプ別合計
+    pub dropped_nodes: Vec<DroppedNode>,
+    pub dropped_entries: usize,
+}
+
+impl RolloverPlan {
+    pub fn summary(
+        &self,
+        dry_run: bool,
+        account_types: &HashMap<Uuid, AccountType>,
+    ) -> RolloverSummary {
+        let mut totals = PlanResultTotals::default();
+        for entry in &self.entries {
+            if let Some(account_type) = account_types.get(&entry.account_item_id) {
+                totals.add(&entry.entry_category, account_type, entry.amount);
+            }
+        }
+
+        RolloverSummary {
+            dry_run,
+            node_count: self.nodes.len(),
+            entry_count: self.entries.len(),
+            totals: totals.statement(),
+            dropped_nodes: self.dropped_nodes.clone(),
+            dropped_entries: self.dropped_entries,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::plan_nodes::NodeType;
+    use crate::domain::test_fixtures::{entry, month, node, scenario};
+
+    fn fiscal_year(year: i32) -> Scenario {
+        scenario(
+            month(year, 4),
+            NaiveDate::from_ymd_opt(year + 1, 3, 31).unwrap(),
+        )
+    }
+
+    #[test]
+    fn plan_shifts_months_into_the_new_period() {
+        let (source, target) = (fiscal_year(2026), fiscal_year(2027));
+        let ini = node("Ini", None, NodeType::Initiative);
+        let prj = node("Prj", Some(&ini), NodeType::Project);
+        let job = node("Job", Some(&prj), NodeType::Job);
+        let entries = vec![entry(
+            &job,
+            Uuid::nil(),
+            month(2026, 5),
+            EntryCategory::Plan,
+            100,
+        )];
+        let options = RolloverOptions {
+            shift_months: true,
+            ..Default::default()
+        };
+
+        let plan = plan(
+            &source,
+            &target,
+            &[ini, prj, job.clone()],
+            &entries,
+            &options,
+            &HashSet::from([job.service_id.unwrap()]),
+            &HashMap::new(),
+            Uuid::nil(),
+        );
+
+        assert_eq!(plan.nodes.len(), 3);
+        assert!(plan.nodes.iter().all(|n| n.scenario_id == target.id));
+        let new_job = plan
+            .nodes
+            .iter()
+            .find(|n| n.lineage_id == job.lineage_id)
+            .unwrap();
+        assert_eq!(plan.entries.len(), 1);
+        assert_eq!(plan.entries[0].node_id, new_job.id);
+        assert_eq!(plan.entries[0].target_month, month(2027, 5));
+        assert_eq!(plan.dropped_entries, 0);
+    }
+
+    #[test]
+    fn plan_drops_shifted_entries_outside_the_new_period() {
+        let source = fiscal_year(2026);
+        let target = scenario(
+            month(2027, 4),
+            NaiveDate::from_ymd_opt(2027, 9, 30).unwrap(),
+        );
+        let ini = node("Ini", None, NodeType::Initiative);
+        let job = node("Job", Some(&ini), NodeType::Job);
+        let entries = vec![
+            entry(&job, Uuid::nil(), month(2026, 5), EntryCategory::Plan, 100),
+            entry(&job, Uuid::nil(), month(2026, 11), EntryCategory::Plan, 200),
+        ];
+        let options = RolloverOptions {
+            shift_months: true,
+            ..Default::default()
+        };
+
+        let plan = plan(
+            &source,
+            &target,
+            &[ini, job.clone()],
+            &entries,
+            &options,
+            &HashSet::from([job.service_id.unwrap()]),
+            &HashMap::new(),
+            Uuid::nil(),
+        );
+
+        assert_eq!(plan.entries.len(), 1);
+        assert_eq!(plan.entries[0].target_month, month(2027, 5));
+        assert_eq!(plan.dropped_entries, 1);
+    }
+
+    #[test]
+    fn plan_drops_closed_services_and_their_orphans() {
+        let (source, target) = (fiscal_year(2026), fiscal_year(2026));
+        let ini = node("Ini", None, NodeType::Initiative);
+        let prj = node("Prj", Some(&ini), NodeType::Project);
+        let closed = node("Closed", Some(&prj), NodeType::Job);
+        let options = RolloverOptions {
+            drop_closed: true,
+            ..Default::default()
+        };
+
+        let plan = plan(
+            &source,
+            &target,
+            &[ini, prj, closed.clone()],
+            &[entry(
+                &closed,
+                Uuid::nil(),
+                month(2026, 4),
+                EntryCategory::Plan,
+                100,
+            )],
+            &options,
+            &HashSet::new(),
+            &HashMap::new(),
+            Uuid::nil(),
+        );
+
+        assert_eq!(plan.nodes.len(), 2);
+        assert!(plan.entries.is_empty());
+        assert_eq!(plan.dropped_nodes.len(), 1);
+        assert_eq!(plan.dropped_nodes[0].node_id, closed.id);
+        assert_eq!(plan.dropped_nodes[0].reason, DropReason::ClosedService);
+    }
+
+    #[test]
+    fn plan_copies_results_as_plan_with_uplift() {
+        let (source, target) = (fiscal_year(2026), fiscal_year(2026));
+        let revenue = Uuid::new_v4();
+        let ini = node("Ini", None, NodeType::Initiative);
+        let job = node("Job", Some(&ini), NodeType::Job);
+        let entries = vec![
+            entry(&job, revenue, month(2026, 4), EntryCategory::Plan, 100),
+            entry(&job, revenue, month(2026, 4), EntryCategory::Result, 200),
+        ];
+        let options = RolloverOptions {
+            entry_mode: RolloverEntryMode::ResultAsPlan,
+            uplift: HashMap::from([(AccountType::Revenue, Decimal::from(10))]),
+            ..Default::default()
+        };
+
+        let plan = plan(
+            &source,
+            &target,
+            &[ini, job.clone()],
+            &entries,
+            &options,
+            &HashSet::from([job.service_id.unwrap()]),
+            &HashMap::from([(revenue, AccountType::Revenue)]),
+            Uuid::nil(),
+        );
+
+        assert_eq!(plan.entries.len(), 1);
+        assert_eq!(plan.entries[0].entry_category, EntryCategory::Plan);
+        assert_eq!(plan.entries[0].amount, Decimal::from(220));
+    }
+
+    #[test]
+    fn plan_drops_entries_outside_the_period_without_shifting() {
+        let source = fiscal_year(2026);
+        let target = scenario(
+            month(2026, 10),
+            NaiveDate::from_ymd_opt(2027, 9, 30).unwrap(),
+        );
+        let ini = node("Ini", None, NodeType::Initiative);
+        let job = node("Job", Some(&ini), NodeType::Job);
+        let entries = vec![
+            entry(&job, Uuid::nil(), month(2026, 5), EntryCategory::Plan, 100),
+            entry(&job, Uuid::nil(), month(2026, 11), EntryCategory::Plan, 200),
+        ];
+
+        let plan = plan(
+            &source,
+            &target,
+            &[ini, job.clone()],
+            &entries,
+            &RolloverOptions::default(),
+            &HashSet::from([job.service_id.unwrap()]),
+            &HashMap::new(),
+            Uuid::nil(),
+        );
+
+        assert_eq!(plan.entries.len(), 1);
+        assert_eq!(plan.entries[0].target_month, month(2026, 11));
+        assert_eq!(plan.dropped_entries, 1);
+    }
+
+    #[test]
+    fn options_reject_uplift_out_of_range() {
+        let options = |rate: i64| RolloverOptions {
+            uplift: HashMap::from([(AccountType::Revenue, Decimal::from(rate))]),
+            ..Default::default()
+        };
+
+        assert!(options(-100).validate().is_ok());
+        assert!(options(1000).validate().is_ok());
+        assert!(options(-150).validate().is_err());
+        assert!(options(1_000_000_000).validate().is_err());
+    }
+}
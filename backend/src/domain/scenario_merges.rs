@@ -0,0 +1,597 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::domain::pl_entries::{EntryCategory, PlEntry};
+use crate::domain::plan_nodes::{self, PlanNode};
+use crate::domain::scenarios::Scenario;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum MergeChangeKind {
+    NodeAdded,     // 分岐シナリオで追加されたノード (Entryごと取り込む)
+    NodeRemoved,   // 分岐シナリオで削除されたノード
+    NodeRenamed,   // 分岐シナリオでタイトルが変更されたノード
+    NodeMoved,     // 分岐シナリオで親が変更されたノード
+    AmountChanged, // 分岐シナリオで金額が変更・追加されたセル
+}
+
+/// 分岐シナリオと分岐元の差分1件
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeChange {
+    pub key: String, // 取り込む差分を選ぶためのキー
+    pub kind: MergeChangeKind,
+    pub lineage_id: Uuid,
+    pub title: String,
+    pub previous_title: Option<String>, // 分岐元のタイトル (NodeRenamed)
+
+    // NodeMovedのみ (Rootの場合はnull)
+    pub parent_lineage_id: Option<Uuid>,
+    pub previous_parent_lineage_id: Option<Uuid>,
+
+    // AmountChangedのみ
+    pub account_item_id: Option<Uuid>,
+    pub target_month: Option<NaiveDate>,
+    pub entry_category: Option<EntryCategory>,
+    pub base_amount: Option<Decimal>, // 分岐元の金額 (Entryがない場合はnull)
+    pub branch_amount: Option<Decimal>,
+
+    // 分岐した後に分岐元でも同じ箇所が変更されている
+    pub conflict: bool,
+}
+
+impl MergeChange {
+    fn node(kind: MergeChangeKind, node: &PlanNode, conflict: bool) -> Self {
+        let prefix = match kind {
+            MergeChangeKind::NodeRenamed => "title",
+            MergeChangeKind::NodeMoved => "parent",
+            _ => "node",
+        };
+
+        Self {
+            key: format!("{}:{}", prefix, node.lineage_id),
+            kind,
+            lineage_id: node.lineage_id,
+            title: node.title.clone(),
+            previous_title: None,
+            parent_lineage_id: None,
+            previous_parent_lineage_id: None,
+            account_item_id: None,
+            target_month: None,
+            entry_category: None,
+            base_amount: None,
+            branch_amount: None,
+            conflict,
+        }
+    }
+}
+
+/// 取り込んだ差分と、衝突のため取り込まなかった差分
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeReport {
+    pub applied: Vec<MergeChange>,
+    pub conflicts: Vec<MergeChange>,
+}
+
+/// 差分を取る片方のシナリオのノード・Entry
+/// changed_entry_idsは分岐した後に変更されたEntry
+pub struct MergeSide<'a> {
+    pub nodes: &'a [PlanNode],
+    pub entries: &'a [PlEntry],
+    pub changed_entry_ids: &'a HashSet<Uuid>,
+}
+
+type CellKey = (Uuid, Uuid, NaiveDate, EntryCategory);
+
+fn cell_key(lineage_id: Uuid, entry: &PlEntry) -> CellKey {
+    (
+        lineage_id,
+        entry.account_item_id,
+        entry.target_month,
+        entry.entry_category.clone(),
+    )
+}
+
+/// 分岐シナリオで行われた変更をlineage_idとセル (科目・月・区分) 単位で洗い出す
+/// 分岐元だけで行われた変更は差分に含めない
+pub fn diff(branch: &Scenario, base: MergeSide, branch_side: MergeSide) -> Vec<MergeChange> {
+    let base_by_lineage: HashMap<Uuid, &PlanNode> =
+        base.nodes.iter().map(|n| (n.lineage_id, n)).collect();
+    let branch_lineages: HashSet<Uuid> = branch_side.nodes.iter().map(|n| n.lineage_id).collect();
+
+    let base_lineages: HashMap<Uuid, Uuid> =
+        base.nodes.iter().map(|n| (n.id, n.lineage_id)).collect();
+    let branch_lineage_by_id: HashMap<Uuid, Uuid> = branch_side
+        .nodes
+        .iter()
+        .map(|n| (n.id, n.lineage_id))
+        .collect();
+    let base_cells: HashMap<CellKey, &PlEntry> = base
+        .entries
+        .iter()
+        .filter_map(|e| {
+            base_lineages
+                .get(&e.node_id)
+                .map(|lineage_id| (cell_key(*lineage_id, e), e))
+        })
+        .collect();
+
+    let mut branch_entries: HashMap<Uuid, Vec<&PlEntry>> = HashMap::new();
+    for entry in branch_side.entries {
+        branch_entries.entry(entry.node_id).or_default().push(entry);
+    }
+
+    let mut changes = Vec::new();
+
+    for (node, _) in plan_nodes::depth_first(branch_side.nodes) {
+        let Some(base_node) = base_by_lineage.get(&node.lineage_id) else {
+            changes.push(MergeChange::node(MergeChangeKind::NodeAdded, node, false));
+            continue;
+        };
+
+        // 分岐時にコピーしたノードはcreated_atとupdated_atが同じ
+        if base_node.title != node.title && node.updated_at > node.created_at {
+            let mut change = MergeChange::node(
+                MergeChangeKind::NodeRenamed,
+                node,
+                base_node.updated_at > branch.created_at,
+            );
+            change.previous_title = Some(base_node.title.clone());
+            changes.push(change);
+        }
+
+        // 親はlineage_idで比べる
+        let parent_lineage = node
+            .parent_id
+            .and_then(|pid| branch_lineage_by_id.get(&pid).copied());
+        let base_parent_lineage = base_node
+            .parent_id
+            .and_then(|pid| base_lineages.get(&pid).copied());
+        if parent_lineage != base_parent_lineage && node.updated_at > node.created_at {
+            let mut change = MergeChange::node(
+                MergeChangeKind::NodeMoved,
+                node,
+                base_node.updated_at > branch.created_at,
+            );
+            change.parent_lineage_id = parent_lineage;
+            change.previous_parent_lineage_id = base_parent_lineage;
+            changes.push(change);
+        }
+
+        let mut entries = branch_entries.remove(&node.id).unwrap_or_default();
+        entries.sort_by_key(|e| {
+            (
+                e.target_month,
+                e.account_item_id,
+                e.entry_category == EntryCategory::Result,
+            )
+        });
+
+        for entry in entries {
+            // 分岐時にコピーしたEntryは履歴を持たない
+            if !branch_side.changed_entry_ids.contains(&entry.id) {
+                continue;
+            }
+
+            let base_entry = base_cells.get(&cell_key(node.lineage_id, entry));
+            if base_entry.is_some_and(|b| b.amount == entry.amount) {
+                continue;
+            }
+
+            changes.push(MergeChange {
+                key: format!(
+                    "cell:{}:{}:{}:{:?}",
+                    node.lineage_id,
+                    entry.account_item_id,
+                    entry.target_month,
+                    entry.entry_category
+                ),
+                kind: MergeChangeKind::AmountChanged,
+                lineage_id: node.lineage_id,
+                title: node.title.clone(),
+                previous_title: None,
+                parent_lineage_id: None,
+                previous_parent_lineage_id: None,
+                account_item_id: Some(entry.account_item_id),
+                target_month: Some(entry.target_month),
+                entry_category: Some(entry.entry_category.clone()),
+                base_amount: base_entry.map(|b| b.amount),
+                branch_amount: Some(entry.amount),
+                conflict: base_entry.is_some_and(|b| base.changed_entry_ids.contains(&b.id)),
+            });
+        }
+    }
+
+    // 分岐した後に分岐元で追加されたノードは削除扱いにしない
+    // その下に追加されたノードがある場合は、削除すると他の作業を消してしまうので衝突とする
+    for (node, _) in plan_nodes::depth_first(base.nodes) {
+        if !branch_lineages.contains(&node.lineage_id) && node.created_at <= branch.created_at {
+            let added_below = plan_nodes::subtree_ids(base.nodes, node.id)
+                .iter()
+                .filter_map(|id| base.nodes.iter().find(|n| n.id == *id))
+                .any(|n| n.created_at > branch.created_at);
+            changes.push(MergeChange::node(
+                MergeChangeKind::NodeRemoved,
+                node,
+                node.updated_at > branch.created_at || added_below,
+            ));
+        }
+    }
+
+    changes
+}
+
+/// 差分を取り込む先のノードID (権限の確認に使う)
+/// 分岐シナリオで追加されたノードは、取り込み先に存在する最も近い祖先 (Noneの場合はRoot)
+pub fn target_anchor(base: &[PlanNode], branch: &[PlanNode], lineage_id: Uuid) -> Option<Uuid> {
+    let base_ids: HashMap<Uuid, Uuid> = base.iter().map(|n| (n.lineage_id, n.id)).collect();
+    if let Some(id) = base_ids.get(&lineage_id) {
+        return Some(*id);
+    }
+
+    let branch_by_id: HashMap<Uuid, &PlanNode> = branch.iter().map(|n| (n.id, n)).collect();
+    let mut visited = HashSet::new();
+    let mut current = branch
+        .iter()
+        .find(|n| n.lineage_id == lineage_id)
+        .and_then(|n| n.parent_id);
+    while let Some(id) = current.filter(|id| visited.insert(*id)) {
+        let node = branch_by_id.get(&id)?;
+        if let Some(base_id) = base_ids.get(&node.lineage_id) {
+            return Some(*base_id);
+        }
+        current = node.parent_id;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::plan_nodes::NodeType;
+    use crate::domain::scenarios::ScenarioKind;
+    use crate::domain::test_fixtures::{entry, month, node, scenario};
+    use chrono::Duration;
+
+    fn branch_scenario() -> Scenario {
+        let base = scenario(
+            month(2026, 4),
+            NaiveDate::from_ymd_opt(2027, 3, 31).unwrap(),
+        );
+        Scenario::branch(
+            &base,
+            "What-if".to_string(),
+            None,
+            ScenarioKind::WhatIf,
+            Uuid::nil(),
+        )
+        .unwrap()
+    }
+
+    // 分岐より前に作成された分岐元のツリー
+    fn base_tree() -> Vec<PlanNode> {
+        let ini = node("Ini", None, NodeType::Initiative);
+        let prj = node("Prj", Some(&ini), NodeType::Project);
+        let prj2 = node("Prj2", Some(&ini), NodeType::Project);
+        let job = node("Job", Some(&prj), NodeType::Job);
+        let mut nodes = vec![ini, prj, prj2, job];
+        for n in nodes.iter_mut() {
+            n.created_at -= Duration::hours(1);
+            n.updated_at = n.created_at;
+        }
+        nodes
+    }
+
+    // 分岐時のコピー (created_atとupdated_atが同じ)
+    fn copy(branch: &Scenario, nodes: &[PlanNode]) -> Vec<PlanNode> {
+        let ids: HashMap<Uuid, Uuid> = nodes.iter().map(|n| (n.id, Uuid::new_v4())).collect();
+        nodes
+            .iter()
+            .map(|n| PlanNode {
+                id: ids[&n.id],
+                scenario_id: branch.id,
+                parent_id: n.parent_id.map(|pid| ids[&pid]),
+                created_at: branch.created_at,
+                updated_at: branch.created_at,
+                ..n.clone()
+            })
+            .collect()
+    }
+
+    fn find<'a>(nodes: &'a [PlanNode], title: &str) -> &'a PlanNode {
+        nodes.iter().find(|n| n.title == title).unwrap()
+    }
+
+    fn side<'a>(nodes: &'a [PlanNode], changed: &'a HashSet<Uuid>) -> MergeSide<'a> {
+        MergeSide {
+            nodes,
+            entries: &[],
+            changed_entry_ids: changed,
+        }
+    }
+
+    #[test]
+    fn diff_reports_nodes_added_in_the_branch() {
+        let branch = branch_scenario();
+        let base_nodes = base_tree();
+        let mut branch_nodes = copy(&branch, &base_nodes);
+        let added = node("Job2", Some(find(&branch_nodes, "Prj")), NodeType::Job);
+        branch_nodes.push(added.clone());
+        let none = HashSet::new();
+
+        let changes = diff(
+            &branch,
+            side(&base_nodes, &none),
+            side(&branch_nodes, &none),
+        );
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, MergeChangeKind::NodeAdded);
+        assert_eq!(changes[0].lineage_id, added.lineage_id);
+        assert!(!changes[0].conflict);
+    }
+
+    #[test]
+    fn diff_reports_nodes_removed_in_the_branch() {
+        let branch = branch_scenario();
+        let base_nodes = base_tree();
+        let branch_nodes: Vec<PlanNode> = copy(&branch, &base_nodes)
+            .into_iter()
+            .filter(|n| n.title != "Prj" && n.title != "Job")
+            .collect();
+        let none = HashSet::new();
+
+        let changes = diff(
+            &branch,
+            side(&base_nodes, &none),
+            side(&branch_nodes, &none),
+        );
+
+        // 親から順に並ぶ
+        let titles: Vec<&str> = changes.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, vec!["Prj", "Job"]);
+        assert!(
+            changes
+                .iter()
+                .all(|c| c.kind == MergeChangeKind::NodeRemoved && !c.conflict)
+        );
+    }
+
+    #[test]
+    fn diff_ignores_nodes_added_to_the_base_after_the_branch_point() {
+        let branch = branch_scenario();
+        let mut base_nodes = base_tree();
+        let branch_nodes = copy(&branch, &base_nodes);
+        let mut later = node("Job3", Some(find(&base_nodes, "Prj")), NodeType::Job);
+        later.created_at = branch.created_at + Duration::minutes(1);
+        later.updated_at = later.created_at;
+        base_nodes.push(later);
+        let none = HashSet::new();
+
+        let changes = diff(
+            &branch,
+            side(&base_nodes, &none),
+            side(&branch_nodes, &none),
+        );
+
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn diff_reports_renames_made_in_the_branch() {
+        let branch = branch_scenario();
+        let mut base_nodes = base_tree();
+        let mut branch_nodes = copy(&branch, &base_nodes);
+        let later = branch.created_at + Duration::minutes(1);
+        for n in branch_nodes.iter_mut().filter(|n| n.title != "Ini") {
+            n.title = format!("{} (renamed)", n.title);
+            n.updated_at = later;
+        }
+        // 分岐した後に分岐元でもJobを変更している
+        for n in base_nodes.iter_mut().filter(|n| n.title == "Job") {
+            n.updated_at = later;
+        }
+        let none = HashSet::new();
+
+        let changes = diff(
+            &branch,
+            side(&base_nodes, &none),
+            side(&branch_nodes, &none),
+        );
+
+        assert_eq!(changes.len(), 3);
+        assert!(
+            changes
+                .iter()
+                .all(|c| c.kind == MergeChangeKind::NodeRenamed)
+        );
+        let prj = changes.iter().find(|c| c.title == "Prj (renamed)").unwrap();
+        assert_eq!(prj.key, format!("title:{}", prj.lineage_id));
+        assert_eq!(prj.previous_title.as_deref(), Some("Prj"));
+        assert!(!prj.conflict);
+        let job = changes.iter().find(|c| c.title == "Job (renamed)").unwrap();
+        assert!(job.conflict);
+    }
+
+    #[test]
+    fn diff_reports_amounts_changed_in_the_branch() {
+        let branch = branch_scenario();
+        let base_nodes = base_tree();
+        let branch_nodes = copy(&branch, &base_nodes);
+        let revenue = Uuid::new_v4();
+        let (base_job, branch_job) = (find(&base_nodes, "Job"), find(&branch_nodes, "Job"));
+        let base_entries = vec![
+            entry(base_job, revenue, month(2026, 4), EntryCategory::Plan, 100),
+            entry(base_job, revenue, month(2026, 5), EntryCategory::Plan, 100),
+        ];
+        let branch_entries = vec![
+            // 分岐時のコピーのまま
+            entry(
+                branch_job,
+                revenue,
+                month(2026, 4),
+                EntryCategory::Plan,
+                100,
+            ),
+            // 分岐元でも変更されている
+            entry(
+                branch_job,
+                revenue,
+                month(2026, 5),
+                EntryCategory::Plan,
+                150,
+            ),
+            // 分岐シナリオで追加した
+            entry(branch_job, revenue, month(2026, 6), EntryCategory::Plan, 80),
+        ];
+        let base_changed = HashSet::from([base_entries[1].id]);
+        let branch_changed: HashSet<Uuid> = branch_entries.iter().map(|e| e.id).collect();
+
+        let changes = diff(
+            &branch,
+            MergeSide {
+                nodes: &base_nodes,
+                entries: &base_entries,
+                changed_entry_ids: &base_changed,
+            },
+            MergeSide {
+                nodes: &branch_nodes,
+                entries: &branch_entries,
+                changed_entry_ids: &branch_changed,
+            },
+        );
+
+        assert_eq!(changes.len(), 2);
+        assert!(
+            changes
+                .iter()
+                .all(|c| c.kind == MergeChangeKind::AmountChanged)
+        );
+        assert_eq!(changes[0].target_month, Some(month(2026, 5)));
+        assert_eq!(changes[0].base_amount, Some(Decimal::from(100)));
+        assert_eq!(changes[0].branch_amount, Some(Decimal::from(150)));
+        assert!(changes[0].conflict);
+        assert_eq!(changes[1].target_month, Some(month(2026, 6)));
+        assert_eq!(changes[1].base_amount, None);
+        assert!(!changes[1].conflict);
+    }
+
+    #[test]
+    fn target_anchor_resolves_the_nearest_existing_ancestor() {
+        let branch = branch_scenario();
+        let base_nodes = base_tree();
+        let mut branch_nodes = copy(&branch, &base_nodes);
+        let sub = node(
+            "Sub",
+            Some(find(&branch_nodes, "Prj")),
+            NodeType::SubProject,
+        );
+        let job = node("Job2", Some(&sub), NodeType::Job);
+        let ini2 = node("Ini2", None, NodeType::Initiative);
+        branch_nodes.extend([sub, job.clone(), ini2.clone()]);
+
+        let prj = find(&base_nodes, "Prj");
+        assert_eq!(
+            target_anchor(&base_nodes, &branch_nodes, prj.lineage_id),
+            Some(prj.id)
+        );
+        // 分岐シナリオで追加したノードの下に追加したノードは、分岐元に存在する祖先になる
+        assert_eq!(
+            target_anchor(&base_nodes, &branch_nodes, job.lineage_id),
+            Some(prj.id)
+        );
+        assert_eq!(
+            target_anchor(&base_nodes, &branch_nodes, ini2.lineage_id),
+            None
+        );
+    }
+
+    #[test]
+    fn diff_flags_removal_of_a_node_with_children_added_later_as_conflict() {
+        let branch = branch_scenario();
+        let mut base_nodes = base_tree();
+        let branch_nodes: Vec<PlanNode> = copy(&branch, &base_nodes)
+            .into_iter()
+            .filter(|n| n.title != "Prj" && n.title != "Job")
+            .collect();
+        let mut later = node("Job3", Some(find(&base_nodes, "Prj")), NodeType::Job);
+        later.created_at = branch.created_at + Duration::minutes(1);
+        later.updated_at = later.created_at;
+        base_nodes.push(later);
+        let none = HashSet::new();
+
+        let changes = diff(
+            &branch,
+            side(&base_nodes, &none),
+            side(&branch_nodes, &none),
+        );
+
+        let prj = changes
+            .iter()
+            .find(|c| c.title == "Prj")
+            .expect("Prj should be removed");
+        assert!(prj.conflict);
+        assert!(!changes.iter().any(|c| c.title == "Job3"));
+    }
+
+    #[test]
+    fn diff_reports_moves_made_in_the_branch() {
+        let branch = branch_scenario();
+        let mut base_nodes = base_tree();
+        let mut branch_nodes = copy(&branch, &base_nodes);
+        let prj2_id = find(&branch_nodes, "Prj2").id;
+        let later = branch.created_at + Duration::minutes(1);
+        for n in branch_nodes.iter_mut().filter(|n| n.title == "Job") {
+            n.parent_id = Some(prj2_id);
+            n.updated_at = later;
+        }
+        // 分岐した後に分岐元でもJobを変更している
+        for n in base_nodes.iter_mut().filter(|n| n.title == "Job") {
+            n.updated_at = later;
+        }
+        let none = HashSet::new();
+
+        let changes = diff(
+            &branch,
+            side(&base_nodes, &none),
+            side(&branch_nodes, &none),
+        );
+
+        assert_eq!(changes.len(), 1);
+        let moved = &changes[0];
+        assert_eq!(moved.kind, MergeChangeKind::NodeMoved);
+        assert_eq!(moved.key, format!("parent:{}", moved.lineage_id));
+        assert_eq!(
+            moved.parent_lineage_id,
+            Some(find(&base_nodes, "Prj2").lineage_id)
+        );
+        assert_eq!(
+            moved.previous_parent_lineage_id,
+            Some(find(&base_nodes, "Prj").lineage_id)
+        );
+        assert!(moved.conflict);
+    }
+
+    #[test]
+    fn diff_ignores_moves_made_only_in_the_base() {
+        let branch = branch_scenario();
+        let mut base_nodes = base_tree();
+        let branch_nodes = copy(&branch, &base_nodes);
+        let prj2_id = find(&base_nodes, "Prj2").id;
+        for n in base_nodes.iter_mut().filter(|n| n.title == "Job") {
+            n.parent_id = Some(prj2_id);
+            n.updated_at = branch.created_at + Duration::minutes(1);
+        }
+        let none = HashSet::new();
+
+        let changes = diff(
+            &branch,
+            side(&base_nodes, &none),
+            side(&branch_nodes, &none),
+        );
+
+        assert!(changes.is_empty());
+    }
+}
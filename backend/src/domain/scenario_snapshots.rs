@@ -0,0 +1,67 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::history::ChangeType;
+use crate::domain::pl_entries::EntryCategory;
+use crate::domain::plan_nodes::NodeType;
+use crate::domain::scenarios::ScenarioKind;
+
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// 環境をまたいで持ち運べるシナリオのアーカイブ
+/// 科目はcode、サービスはslug、ユーザーはemailで参照し、環境ごとに異なるIDは持たない
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioSnapshot {
+    pub format_version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub scenario: SnapshotScenario,
+    pub nodes: Vec<SnapshotNode>, // 親が子より先に並ぶ
+    pub entries: Vec<SnapshotEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotScenario {
+    pub name: String,
+    pub description: Option<String>,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    #[serde(default)]
+    pub kind: ScenarioKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotNode {
+    // アーカイブ内でのみ使う参照キー (エクスポート元のID)
+    pub id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub lineage_id: Uuid,
+
+    pub title: String,
+    pub description: Option<String>,
+    pub node_type: NodeType,
+    pub display_order: i32,
+    pub service_slug: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    pub node_id: Uuid, // SnapshotNode.id
+    pub account_item_code: String,
+    pub target_month: NaiveDate,
+    pub entry_category: EntryCategory,
+    pub amount: Decimal,
+    pub description: Option<String>,
+    pub histories: Vec<SnapshotHistory>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotHistory {
+    pub change_type: ChangeType,
+    pub previous_amount: Option<Decimal>,
+    pub new_amount: Decimal,
+    pub changed_at: DateTime<Utc>,
+    pub changed_by: Option<String>, // email
+    pub operation_source: Option<String>,
+}
@@ -1,7 +1,103 @@
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
 use uuid::Uuid;
 
+use crate::domain::user::UserRole;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "scenario_kind")]
+pub enum ScenarioKind {
+    #[default]
+    Budget, // 予算
+    Forecast, // 見通し
+    WhatIf,   // 試算
+}
+
+/// 承認フローの状態 (Draft → Submitted → Approved / Rejected)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "scenario_status")]
+pub enum ScenarioStatus {
+    #[default]
+    Draft,
+    Submitted,
+    Approved,
+    Rejected, // 差し戻し (再度編集して提出できる)
+}
+
+impl ScenarioStatus {
+    /// 作成中・差し戻し中のみノードとEntryを編集できる
+    pub fn is_editable(&self) -> bool {
+        matches!(self, ScenarioStatus::Draft | ScenarioStatus::Rejected)
+    }
+
+    /// 提出はManager以上、承認・差し戻し・承認の取り消し (作成中に戻す) はAdminのみ行える
+    /// 差し戻しにはコメントが必要
+    pub fn ensure_transition(
+        &self,
+        to: ScenarioStatus,
+        role: &UserRole,
+        comment: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let allowed_roles: &[UserRole] = match (self, to) {
+            (ScenarioStatus::Draft | ScenarioStatus::Rejected, ScenarioStatus::Submitted) => {
+                &[UserRole::Admin, UserRole::Manager]
+            }
+            (ScenarioStatus::Submitted, ScenarioStatus::Approved | ScenarioStatus::Rejected)
+            | (ScenarioStatus::Approved, ScenarioStatus::Draft) => &[UserRole::Admin],
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Cannot change scenario status from {:?} to {:?}",
+                    self,
+                    to
+                ));
+            }
+        };
+
+        if !allowed_roles.contains(role) {
+            return Err(anyhow::anyhow!("Permission denied"));
+        }
+
+        if to == ScenarioStatus::Rejected && comment.is_none_or(|c| c.trim().is_empty()) {
+            return Err(anyhow::anyhow!("Comment is required to reject a scenario"));
+        }
+
+        Ok(())
+    }
+}
+
+/// 承認フローの遷移履歴
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ScenarioStatusTransition {
+    pub id: Uuid,
+    pub scenario_id: Uuid,
+    pub from_status: ScenarioStatus,
+    pub to_status: ScenarioStatus,
+    pub comment: Option<String>,
+    pub acted_at: DateTime<Utc>,
+    pub acted_by: Uuid,
+}
+
+impl ScenarioStatusTransition {
+    pub fn new(
+        scenario_id: Uuid,
+        from_status: ScenarioStatus,
+        to_status: ScenarioStatus,
+        comment: Option<String>,
+        user_id: Uuid,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            scenario_id,
+            from_status,
+            to_status,
+            comment,
+            acted_at: Utc::now(),
+            acted_by: user_id,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Scenario {
     pub id: Uuid,
@@ -12,6 +108,9 @@ pub struct Scenario {
     pub end_date: NaiveDate,
     pub is_locked: bool,  // シナリオの締めフラグ
     pub is_current: bool, // 現在作成中のシナリオかどうかのフラグ
+    pub kind: ScenarioKind,
+    pub parent_scenario_id: Option<Uuid>, // 分岐元のシナリオ
+    pub status: ScenarioStatus,
 
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -30,6 +129,7 @@ impl Scenario {
         description: Option<String>,
         start_date: NaiveDate,
         end_date: NaiveDate,
+        kind: ScenarioKind,
         user_id: Uuid,
     ) -> anyhow::Result<Self> {
         if name.trim().is_empty() {
@@ -48,6 +148,9 @@ impl Scenario {
             end_date,
             is_locked: false,
             is_current: false,
+            kind,
+            parent_scenario_id: None,
+            status: ScenarioStatus::Draft,
             created_at: Utc::now(),
             updated_at: Utc::now(),
             created_by: user_id,
@@ -56,12 +159,174 @@ impl Scenario {
             deleted_by: None,
         })
     }
+
+    /// 作成中のシナリオを変えずに試算するための分岐シナリオ (期間は分岐元と同じ)
+    pub fn branch(
+        parent: &Scenario,
+        name: String,
+        description: Option<String>,
+        kind: ScenarioKind,
+        user_id: Uuid,
+    ) -> anyhow::Result<Self> {
+        let mut scenario = Self::new(
+            name,
+            description,
+            parent.start_date,
+            parent.end_date,
+            kind,
+            user_id,
+        )?;
+        scenario.parent_scenario_id = Some(parent.id);
+        Ok(scenario)
+    }
+
+    /// 作成中のシナリオは誰でも、作成中でない分岐シナリオは作成者のみ編集できる
+    pub fn is_editable_by(&self, user_id: Uuid) -> bool {
+        self.is_current || (self.parent_scenario_id.is_some() && self.created_by == user_id)
+    }
+
+    /// 作成中でない、user_idが作成した分岐シナリオか
+    pub fn is_private_branch_of(&self, user_id: Uuid) -> bool {
+        self.parent_scenario_id.is_some() && !self.is_current && self.created_by == user_id
+    }
+
+    /// Memberは作成中のシナリオか自分の分岐シナリオからのみ分岐できる
+    pub fn can_branch(&self, user_id: Uuid, role: &UserRole) -> bool {
+        matches!(role, UserRole::Admin | UserRole::Manager) || self.is_editable_by(user_id)
+    }
+
+    /// Entryの対象月が月初日で、シナリオ期間に含まれるかチェックする
+    pub fn validate_target_month(&self, target_month: NaiveDate) -> anyhow::Result<()> {
+        if target_month.day() != 1 {
+            return Err(anyhow::anyhow!(
+                "Target month must be the first day of a month"
+            ));
+        }
+
+        let first_month = self
+            .start_date
+            .with_day(1)
+            .expect("valid first day of month");
+        if target_month < first_month || target_month > self.end_date {
+            return Err(anyhow::anyhow!(
+                "Month {} is outside the scenario period",
+                target_month.format("%Y-%m")
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// シナリオ期間に含まれる月 (月初日) の一覧
+    pub fn months(&self) -> Vec<NaiveDate> {
+        let mut months = Vec::new();
+        let mut month = self
+            .start_date
+            .with_day(1)
+            .expect("valid first day of month");
+        while month <= self.end_date {
+            months.push(month);
+            month = month + Months::new(1);
+        }
+        months
+    }
+}
+
+/// 一覧表示用に承認フローの遷移履歴を添えたシナリオ
+#[derive(Debug, Clone, Serialize)]
+pub struct ScenarioWithTransitions {
+    #[serde(flatten)]
+    pub scenario: Scenario,
+    pub status_transitions: Vec<ScenarioStatusTransition>, // 古い順
 }
 
 #[async_trait::async_trait]
 pub trait ScenarioRepository {
-    async fn create(&self, scenario: &Scenario) -> anyhow::Result<Scenario>;
+    async fn create(&self, tx: &mut PgConnection, scenario: &Scenario) -> anyhow::Result<Scenario>;
     async fn find_all(&self) -> anyhow::Result<Vec<Scenario>>;
     async fn find_by_id(&self, id: Uuid) -> anyhow::Result<Option<Scenario>>;
-    async fn set_current(&self, id: Uuid) -> anyhow::Result<()>;
+    // ノードが属するシナリオ
+    async fn find_by_node_id(
+        &self,
+        tx: &mut PgConnection,
+        node_id: Uuid,
+    ) -> anyhow::Result<Option<Scenario>>;
+    async fn set_current(&self, tx: &mut PgConnection, id: Uuid) -> anyhow::Result<()>;
+    async fn set_locked(
+        &self,
+        id: Uuid,
+        is_locked: bool,
+        user_id: Uuid,
+    ) -> anyhow::Result<Scenario>;
+    // 遷移元の状態のままであれば状態を変更し、遷移履歴を記録する
+    // 承認した場合は締め済みにし、承認を取り消した場合は締めを解除する
+    async fn transition_status(
+        &self,
+        transition: &ScenarioStatusTransition,
+    ) -> anyhow::Result<Scenario>;
+    async fn find_status_transitions(&self) -> anyhow::Result<Vec<ScenarioStatusTransition>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::test_fixtures::{month, scenario};
+
+    #[test]
+    fn validate_target_month_requires_a_month_start_within_the_period() {
+        let fy = scenario(
+            month(2026, 4),
+            NaiveDate::from_ymd_opt(2027, 3, 31).unwrap(),
+        );
+
+        assert!(fy.validate_target_month(month(2026, 4)).is_ok());
+        assert!(fy.validate_target_month(month(2027, 3)).is_ok());
+        assert!(
+            fy.validate_target_month(NaiveDate::from_ymd_opt(2026, 4, 15).unwrap())
+                .is_err()
+        );
+        assert!(fy.validate_target_month(month(2026, 3)).is_err());
+        assert!(fy.validate_target_month(month(2027, 4)).is_err());
+
+        // 月の途中から始まるシナリオでも開始月のEntryは入力できる
+        let mid_month = scenario(
+            NaiveDate::from_ymd_opt(2026, 4, 15).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 9, 30).unwrap(),
+        );
+        assert!(mid_month.validate_target_month(month(2026, 4)).is_ok());
+    }
+
+    #[test]
+    fn can_branch_limits_members_to_the_current_scenario_and_their_own_branches() {
+        let member = Uuid::new_v4();
+        let mut current = scenario(month(2026, 4), month(2027, 3));
+        let other = scenario(month(2026, 4), month(2027, 3));
+        let own_branch = Scenario::branch(
+            &other,
+            "Mine".to_string(),
+            None,
+            ScenarioKind::WhatIf,
+            member,
+        )
+        .unwrap();
+        let others_branch = Scenario::branch(
+            &other,
+            "Theirs".to_string(),
+            None,
+            ScenarioKind::WhatIf,
+            Uuid::new_v4(),
+        )
+        .unwrap();
+
+        assert!(!current.can_branch(member, &UserRole::Member));
+        current.is_current = true;
+        assert!(current.can_branch(member, &UserRole::Member));
+        assert!(own_branch.can_branch(member, &UserRole::Member));
+        assert!(!others_branch.can_branch(member, &UserRole::Member));
+        assert!(!other.can_branch(member, &UserRole::Member));
+        for role in [UserRole::Admin, UserRole::Manager] {
+            assert!(other.can_branch(member, &role));
+            assert!(others_branch.can_branch(member, &role));
+        }
+    }
 }
@@ -0,0 +1,199 @@
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::domain::pl_entries::{EntryCategory, PlEntry};
+use crate::domain::plan_nodes::{self, PlanNode};
+use crate::domain::rollover;
+use crate::domain::scenarios::Scenario;
+
+#[derive(Debug, Clone, Default)]
+pub struct SubtreeCopyOptions {
+    // コピーした起点のノードのタイトル・並び順 (省略時は元のまま)
+    pub title: Option<String>,
+    pub display_order: Option<i32>,
+    // 計画値をコピーする (確定値はコピーしない)
+    pub copy_entries: bool,
+    // 計画値の対象月をずらす月数
+    pub month_offset: i32,
+}
+
+/// サブツリーのコピーで作成するノード・Entry
+#[derive(Debug, Clone)]
+pub struct SubtreeCopyPlan {
+    pub nodes: Vec<PlanNode>, // 親が子より先に並び、先頭が起点のノード
+    pub entries: Vec<PlEntry>,
+    pub dropped_entries: usize, // ずらした結果シナリオの期間から外れたEntryの件数
+}
+
+/// サブツリーのコピーの結果
+#[derive(Debug, Clone, Serialize)]
+pub struct SubtreeCopySummary {
+    pub root: PlanNode,
+    pub node_count: usize,
+    pub entry_count: usize,
+    pub dropped_entries: usize,
+}
+
+/// root_idのノードとその子孫をparent_idの下に複製する
+/// 複製したノードは別の意味のノードとして扱うので、lineage_idは新しく発行する
+#[allow(clippy::too_many_arguments)]
+pub fn plan(
+    scenario: &Scenario,
+    nodes: &[PlanNode],
+    entries: &[PlEntry],
+    root_id: Uuid,
+    parent_id: Option<Uuid>,
+    options: &SubtreeCopyOptions,
+    user_id: Uuid,
+) -> SubtreeCopyPlan {
+    let now = Utc::now();
+    let subtree = plan_nodes::subtree_ids(nodes, root_id);
+    let sources: Vec<PlanNode> = nodes
+        .iter()
+        .filter(|n| subtree.contains(&n.id))
+        .cloned()
+        .collect();
+
+    let mut id_map: HashMap<Uuid, Uuid> = HashMap::new();
+    let mut new_nodes: Vec<PlanNode> = Vec::new();
+
+    // 深さ優先で辿るので起点のノードが先頭になる
+    for (node, _) in plan_nodes::depth_first(&sources) {
+        let new_id = Uuid::new_v4();
+        id_map.insert(node.id, new_id);
+
+        let is_root = node.id == root_id;
+        new_nodes.push(PlanNode {
+            id: new_id,
+            scenario_id: scenario.id,
+            parent_id: if is_root {
+                parent_id
+            } else {
+                node.parent_id.and_then(|pid| id_map.get(&pid).copied())
+            },
+            lineage_id: Uuid::new_v4(),
+            title: match &options.title {
+                Some(title) if is_root => title.clone(),
+                _ => node.title.clone(),
+            },
+            description: node.description.clone(),
+            node_type: node.node_type.clone(),
+            display_order: match options.display_order {
+                Some(order) if is_root => order,
+                _ => node.display_order,
+            },
+            service_id: node.service_id,
+            owner_id: node.owner_id,
+            editor_ids: node.editor_ids.clone(),
+            created_at: now,
+            updated_at: now,
+            created_by: user_id,
+            updated_by: user_id,
+            deleted_at: None,
+            deleted_by: None,
+        });
+    }
+
+    let mut new_entries: Vec<PlEntry> = Vec::new();
+    let mut dropped_entries = 0;
+
+    if options.copy_entries {
+        for entry in entries {
+            let Some(&new_node_id) = id_map.get(&entry.node_id) else {
+                continue;
+            };
+            if entry.entry_category != EntryCategory::Plan {
+                continue;
+            }
+
+            let Some(target_month) =
+                rollover::shift_month(entry.target_month, options.month_offset)
+                    .filter(|month| scenario.validate_target_month(*month).is_ok())
+            else {
+                dropped_entries += 1;
+                continue;
+            };
+
+            new_entries.push(PlEntry {
+                id: Uuid::new_v4(),
+                target_month,
+                entry_category: EntryCategory::Plan,
+                node_id: new_node_id,
+                account_item_id: entry.account_item_id,
+                amount: entry.amount,
+                description: entry.description.clone(),
+                created_at: now,
+                updated_at: now,
+                created_by: user_id,
+                updated_by: user_id,
+            });
+        }
+    }
+
+    SubtreeCopyPlan {
+        nodes: new_nodes,
+        entries: new_entries,
+        dropped_entries,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::plan_nodes::NodeType;
+    use crate::domain::test_fixtures::{entry, month, node, scenario};
+
+    #[test]
+    fn plan_copies_the_subtree_with_new_lineages() {
+        let scenario = scenario(month(2026, 4), month(2027, 3));
+        let ini = node("Ini", None, NodeType::Initiative);
+        let prj = node("Prj", Some(&ini), NodeType::Project);
+        let job = node("Job", Some(&prj), NodeType::Job);
+        let other = node("Other", Some(&ini), NodeType::Project);
+        let nodes = vec![ini.clone(), prj.clone(), job.clone(), other];
+        let account = Uuid::new_v4();
+        let entries = vec![
+            entry(&job, account, month(2026, 4), EntryCategory::Plan, 100),
+            entry(&job, account, month(2027, 3), EntryCategory::Plan, 100),
+            entry(&job, account, month(2026, 4), EntryCategory::Result, 100),
+        ];
+        let options = SubtreeCopyOptions {
+            title: Some("Prj (copy)".to_string()),
+            display_order: Some(9),
+            copy_entries: true,
+            month_offset: 1,
+        };
+
+        let plan = plan(
+            &scenario,
+            &nodes,
+            &entries,
+            prj.id,
+            Some(ini.id),
+            &options,
+            Uuid::nil(),
+        );
+
+        assert_eq!(plan.nodes.len(), 2);
+        let root = &plan.nodes[0];
+        assert_eq!(root.title, "Prj (copy)");
+        assert_eq!(root.display_order, 9);
+        assert_eq!(root.parent_id, Some(ini.id));
+        let copied_job = &plan.nodes[1];
+        assert_eq!(copied_job.title, "Job");
+        assert_eq!(copied_job.parent_id, Some(root.id));
+        assert!(
+            plan.nodes
+                .iter()
+                .all(|n| n.lineage_id != prj.lineage_id && n.lineage_id != job.lineage_id)
+        );
+
+        // 確定値はコピーせず、期間外にずれた計画値は落とす
+        assert_eq!(plan.entries.len(), 1);
+        assert_eq!(plan.entries[0].node_id, copied_job.id);
+        assert_eq!(plan.entries[0].target_month, month(2026, 5));
+        assert_eq!(plan.dropped_entries, 1);
+    }
+}
@@ -0,0 +1,58 @@
+// 単体テストで使うノード・Entryを組み立てる
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::domain::pl_entries::{EntryCategory, PlEntry};
+use crate::domain::plan_nodes::{NodeType, PlanNode};
+use crate::domain::scenarios::{Scenario, ScenarioKind};
+
+pub fn month(year: i32, month: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, 1).unwrap()
+}
+
+pub fn scenario(start_date: NaiveDate, end_date: NaiveDate) -> Scenario {
+    Scenario::new(
+        "FY".to_string(),
+        None,
+        start_date,
+        end_date,
+        ScenarioKind::Budget,
+        Uuid::nil(),
+    )
+    .unwrap()
+}
+
+/// parentの下に作るノード (実体タイプのノードは新しいサービスに紐づける)
+pub fn node(title: &str, parent: Option<&PlanNode>, node_type: NodeType) -> PlanNode {
+    PlanNode::new(
+        Uuid::nil(),
+        parent.map(|p| p.id),
+        None,
+        title.to_string(),
+        None,
+        node_type.clone(),
+        0,
+        node_type.is_entity().then(Uuid::new_v4),
+        Uuid::nil(),
+    )
+    .unwrap()
+}
+
+pub fn entry(
+    node: &PlanNode,
+    account_item_id: Uuid,
+    target_month: NaiveDate,
+    category: EntryCategory,
+    amount: i64,
+) -> PlEntry {
+    PlEntry::new(
+        target_month,
+        category,
+        node.id,
+        account_item_id,
+        Decimal::from(amount),
+        None,
+        Uuid::nil(),
+    )
+}
@@ -60,4 +60,5 @@ pub trait UserRepository: Send + Sync {
     async fn create(&self, user: &User) -> anyhow::Result<User>;
     async fn find_by_email(&self, email: &str) -> anyhow::Result<Option<User>>;
     async fn find_by_id(&self, id: Uuid) -> anyhow::Result<Option<User>>;
+    async fn find_by_ids(&self, ids: Vec<Uuid>) -> anyhow::Result<Vec<User>>;
 }
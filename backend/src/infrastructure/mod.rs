@@ -1 +1,2 @@
 pub mod persistence;
+pub mod xlsx;
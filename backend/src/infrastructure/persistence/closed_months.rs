@@ -0,0 +1,171 @@
+use chrono::NaiveDate;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::domain::closed_months::{ClosedMonth, ClosedMonthRepository, ClosingEvent};
+
+#[derive(Debug, Clone)]
+pub struct ClosedMonthRepositoryImpl {
+    pool: PgPool,
+}
+
+impl ClosedMonthRepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+async fn insert_event(tx: &mut PgConnection, event: &ClosingEvent) -> anyhow::Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO closing_events
+        (
+            id,
+            scenario_id,
+            target_month,
+            action,
+            reason,
+            acted_at,
+            acted_by
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+        event.id,
+        event.scenario_id,
+        event.target_month,
+        event.action as _,
+        event.reason,
+        event.acted_at,
+        event.acted_by
+    )
+    .execute(tx)
+    .await?;
+
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl ClosedMonthRepository for ClosedMonthRepositoryImpl {
+    async fn find_by_scenario_id(&self, scenario_id: Uuid) -> anyhow::Result<Vec<ClosedMonth>> {
+        let recs = sqlx::query_as!(
+            ClosedMonth,
+            r#"
+            SELECT * FROM closed_months
+            WHERE scenario_id = $1
+            ORDER BY target_month
+            "#,
+            scenario_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(recs)
+    }
+
+    async fn is_closed_for_node(
+        &self,
+        tx: &mut PgConnection,
+        node_id: Uuid,
+        target_month: NaiveDate,
+    ) -> anyhow::Result<bool> {
+        let closed = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1
+                FROM closed_months c
+                JOIN plan_nodes n ON n.scenario_id = c.scenario_id
+                WHERE n.id = $1
+                  AND c.target_month = $2
+            ) as "closed!"
+            "#,
+            node_id,
+            target_month
+        )
+        .fetch_one(tx)
+        .await?;
+
+        Ok(closed)
+    }
+
+    async fn close(&self, month: &ClosedMonth, event: &ClosingEvent) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO closed_months
+            (
+                scenario_id,
+                target_month,
+                closed_at,
+                closed_by
+            ) VALUES ($1, $2, $3, $4)
+            ON CONFLICT DO NOTHING
+            "#,
+            month.scenario_id,
+            month.target_month,
+            month.closed_at,
+            month.closed_by
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Err(anyhow::anyhow!("Month is already closed"));
+        }
+
+        insert_event(&mut tx, event).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn reopen(&self, event: &ClosingEvent) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM closed_months
+            WHERE scenario_id = $1
+              AND target_month = $2
+            "#,
+            event.scenario_id,
+            event.target_month
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Err(anyhow::anyhow!("Month is not closed"));
+        }
+
+        insert_event(&mut tx, event).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn find_events(&self, scenario_id: Uuid) -> anyhow::Result<Vec<ClosingEvent>> {
+        let recs = sqlx::query_as!(
+            ClosingEvent,
+            r#"
+            SELECT
+                id,
+                scenario_id,
+                target_month,
+                action as "action: _",
+                reason,
+                acted_at,
+                acted_by
+            FROM closing_events
+            WHERE scenario_id = $1
+            ORDER BY acted_at DESC
+            "#,
+            scenario_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(recs)
+    }
+}
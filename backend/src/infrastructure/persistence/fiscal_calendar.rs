@@ -0,0 +1,60 @@
+use sqlx::PgPool;
+
+use crate::domain::fiscal_calendar::{FiscalCalendar, FiscalCalendarRepository};
+
+#[derive(Debug, Clone)]
+pub struct FiscalCalendarRepositoryImpl {
+    pool: PgPool,
+}
+
+impl FiscalCalendarRepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl FiscalCalendarRepository for FiscalCalendarRepositoryImpl {
+    async fn find(&self) -> anyhow::Result<FiscalCalendar> {
+        let rec = sqlx::query_as!(
+            FiscalCalendar,
+            r#"
+            SELECT
+                fiscal_year_start_month,
+                updated_at,
+                updated_by
+            FROM fiscal_settings
+            WHERE id = 1
+            "#
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(rec)
+    }
+
+    async fn update(&self, calendar: &FiscalCalendar) -> anyhow::Result<FiscalCalendar> {
+        let rec = sqlx::query_as!(
+            FiscalCalendar,
+            r#"
+            UPDATE fiscal_settings
+            SET
+                fiscal_year_start_month = $1,
+                updated_at = $2,
+                updated_by = $3
+            WHERE id = 1
+            RETURNING
+                fiscal_year_start_month,
+                updated_at,
+                updated_by
+            "#,
+            calendar.fiscal_year_start_month,
+            calendar.updated_at,
+            calendar.updated_by
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(rec)
+    }
+}
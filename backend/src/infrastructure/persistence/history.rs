@@ -1,15 +1,16 @@
 use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
 
 use crate::domain::history::{PlEntryHistory, PlEntryHistoryRepository};
 
 #[derive(Debug, Clone)]
 pub struct PlEntryHistoryRepositoryImpl {
-    _pool: PgPool,
+    pool: PgPool,
 }
 
 impl PlEntryHistoryRepositoryImpl {
     pub fn new(pool: PgPool) -> Self {
-        Self { _pool: pool }
+        Self { pool }
     }
 }
 
@@ -44,4 +45,65 @@ impl PlEntryHistoryRepository for PlEntryHistoryRepositoryImpl {
 
         Ok(())
     }
+
+    async fn find_by_entry_ids(&self, entry_ids: Vec<Uuid>) -> anyhow::Result<Vec<PlEntryHistory>> {
+        let recs = sqlx::query_as!(
+            PlEntryHistory,
+            r#"
+            SELECT
+                id,
+                entry_id,
+                change_type as "change_type: _",
+                previous_amount,
+                new_amount,
+                changed_at,
+                changed_by,
+                operation_source
+            FROM pl_entry_histories
+            WHERE entry_id = ANY($1)
+            ORDER BY changed_at
+            "#,
+            &entry_ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(recs)
+    }
+
+    async fn create_many(
+        &self,
+        tx: &mut PgConnection,
+        histories: Vec<PlEntryHistory>,
+    ) -> anyhow::Result<()> {
+        for history in histories {
+            sqlx::query!(
+                r#"
+                INSERT INTO pl_entry_histories
+                (
+                    id,
+                    entry_id,
+                    change_type,
+                    previous_amount,
+                    new_amount,
+                    changed_at,
+                    changed_by,
+                    operation_source
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                "#,
+                history.id,
+                history.entry_id,
+                history.change_type as _,
+                history.previous_amount,
+                history.new_amount,
+                history.changed_at,
+                history.changed_by,
+                history.operation_source,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,153 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::ledger_mappings::{
+    LedgerAccountMapping, LedgerDepartmentMapping, LedgerMappingRepository,
+};
+
+#[derive(Debug, Clone)]
+pub struct LedgerMappingRepositoryImpl {
+    pool: PgPool,
+}
+
+impl LedgerMappingRepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl LedgerMappingRepository for LedgerMappingRepositoryImpl {
+    async fn find_account_mappings(&self) -> anyhow::Result<Vec<LedgerAccountMapping>> {
+        let recs = sqlx::query_as!(
+            LedgerAccountMapping,
+            r#"
+            SELECT * FROM ledger_account_mappings
+            ORDER BY external_code
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(recs)
+    }
+
+    async fn upsert_account_mapping(
+        &self,
+        mapping: &LedgerAccountMapping,
+    ) -> anyhow::Result<LedgerAccountMapping> {
+        let rec = sqlx::query_as!(
+            LedgerAccountMapping,
+            r#"
+            INSERT INTO ledger_account_mappings
+            (
+                id,
+                external_code,
+                account_item_id,
+                created_at,
+                updated_at
+            ) VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (external_code) DO UPDATE
+            SET
+                account_item_id = EXCLUDED.account_item_id,
+                updated_at = EXCLUDED.updated_at
+            RETURNING *
+            "#,
+            mapping.id,
+            mapping.external_code,
+            mapping.account_item_id,
+            mapping.created_at,
+            mapping.updated_at
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(rec)
+    }
+
+    async fn delete_account_mapping(&self, id: Uuid) -> anyhow::Result<()> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM ledger_account_mappings
+            WHERE id = $1
+            "#,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow::anyhow!("Mapping not found"));
+        }
+
+        Ok(())
+    }
+
+    async fn find_department_mappings(&self) -> anyhow::Result<Vec<LedgerDepartmentMapping>> {
+        let recs = sqlx::query_as!(
+            LedgerDepartmentMapping,
+            r#"
+            SELECT * FROM ledger_department_mappings
+            ORDER BY external_code
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(recs)
+    }
+
+    async fn upsert_department_mapping(
+        &self,
+        mapping: &LedgerDepartmentMapping,
+    ) -> anyhow::Result<LedgerDepartmentMapping> {
+        let rec = sqlx::query_as!(
+            LedgerDepartmentMapping,
+            r#"
+            INSERT INTO ledger_department_mappings
+            (
+                id,
+                external_code,
+                lineage_id,
+                service_id,
+                created_at,
+                updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (external_code) DO UPDATE
+            SET
+                lineage_id = EXCLUDED.lineage_id,
+                service_id = EXCLUDED.service_id,
+                updated_at = EXCLUDED.updated_at
+            RETURNING *
+            "#,
+            mapping.id,
+            mapping.external_code,
+            mapping.lineage_id,
+            mapping.service_id,
+            mapping.created_at,
+            mapping.updated_at
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(rec)
+    }
+
+    async fn delete_department_mapping(&self, id: Uuid) -> anyhow::Result<()> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM ledger_department_mappings
+            WHERE id = $1
+            "#,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow::anyhow!("Mapping not found"));
+        }
+
+        Ok(())
+    }
+}
@@ -1,5 +1,8 @@
 pub mod account_item;
+pub mod closed_months;
+pub mod fiscal_calendar;
 pub mod history;
+pub mod ledger_mappings;
 pub mod pl_entries;
 pub mod plan_nodes;
 pub mod scenarios;
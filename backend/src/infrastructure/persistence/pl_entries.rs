@@ -2,7 +2,7 @@ use chrono::NaiveDate;
 use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 
-use crate::domain::pl_entries::{EntryCategory, PlEntry, PlEntryRepository};
+use crate::domain::pl_entries::{EntryCategory, InvalidMonthEntry, PlEntry, PlEntryRepository};
 
 #[derive(Debug, Clone)]
 pub struct PlEntryRepositoryImpl {
@@ -219,6 +219,7 @@ impl PlEntryRepository for PlEntryRepositoryImpl {
             FROM pl_entries e
             JOIN plan_nodes n ON e.node_id = n.id
             WHERE n.scenario_id = $1
+              AND n.deleted_at IS NULL
             ORDER BY e.node_id, e.target_month
             "#,
             scenario_id
@@ -229,7 +230,46 @@ impl PlEntryRepository for PlEntryRepositoryImpl {
         Ok(entries)
     }
 
-    async fn create_many(&self, entries: Vec<PlEntry>) -> anyhow::Result<()> {
+    async fn find_invalid_months(&self) -> anyhow::Result<Vec<InvalidMonthEntry>> {
+        let recs = sqlx::query_as!(
+            InvalidMonthEntry,
+            r#"
+            SELECT
+                e.id as entry_id,
+                s.id as scenario_id,
+                s.name as scenario_name,
+                n.id as node_id,
+                n.title as node_title,
+                e.account_item_id,
+                e.target_month,
+                e.entry_category as "entry_category: _",
+                e.amount,
+                EXTRACT(DAY FROM e.target_month) <> 1 as "not_month_start!",
+                (e.target_month < date_trunc('month', s.start_date)::date
+                    OR e.target_month > s.end_date) as "outside_period!"
+            FROM pl_entries e
+            JOIN plan_nodes n ON e.node_id = n.id
+            JOIN scenarios s ON n.scenario_id = s.id
+            WHERE n.deleted_at IS NULL
+              AND (
+                EXTRACT(DAY FROM e.target_month) <> 1
+                OR e.target_month < date_trunc('month', s.start_date)::date
+                OR e.target_month > s.end_date
+              )
+            ORDER BY s.start_date, s.id, e.node_id, e.target_month
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(recs)
+    }
+
+    async fn create_many(
+        &self,
+        tx: &mut PgConnection,
+        entries: Vec<PlEntry>,
+    ) -> anyhow::Result<()> {
         for entry in entries {
             sqlx::query!(
                 r#"
@@ -258,7 +298,7 @@ impl PlEntryRepository for PlEntryRepositoryImpl {
                 entry.created_by,
                 entry.updated_by
             )
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
         }
         Ok(())
@@ -1,8 +1,9 @@
-use chrono::{DateTime, Utc};
-use sqlx::{PgPool, QueryBuilder};
+use sqlx::{PgConnection, PgPool, QueryBuilder};
 use uuid::Uuid;
 
-use crate::domain::plan_nodes::{NodeType, PlanNode, PlanNodeRepository, UpdatePlanNodeParams};
+use crate::domain::plan_nodes::{
+    PlanNode, PlanNodeRepository, PlanNodeTreeRow, UpdatePlanNodeParams,
+};
 
 #[derive(Debug, Clone)]
 pub struct PlanNodeRepositoryImpl {
@@ -32,13 +33,15 @@ impl PlanNodeRepository for PlanNodeRepositoryImpl {
                 node_type,
                 display_order,
                 service_id,
+                owner_id,
+                editor_ids,
                 created_at,
                 updated_at,
                 created_by,
                 updated_by,
                 deleted_at,
                 deleted_by
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
             RETURNING
                 id,
                 scenario_id,
@@ -49,6 +52,8 @@ impl PlanNodeRepository for PlanNodeRepositoryImpl {
                 node_type as "node_type: _",
                 display_order,
                 service_id,
+                owner_id,
+                editor_ids,
                 created_at,
                 updated_at,
                 created_by,
@@ -65,6 +70,8 @@ impl PlanNodeRepository for PlanNodeRepositoryImpl {
             node.node_type as _,
             node.display_order,
             node.service_id,
+            node.owner_id,
+            &node.editor_ids,
             node.created_at,
             node.updated_at,
             node.created_by,
@@ -78,7 +85,7 @@ impl PlanNodeRepository for PlanNodeRepositoryImpl {
         Ok(rec)
     }
 
-    async fn create_many(&self, nodes: Vec<PlanNode>) -> anyhow::Result<()> {
+    async fn create_many(&self, tx: &mut PgConnection, nodes: Vec<PlanNode>) -> anyhow::Result<()> {
         if nodes.is_empty() {
             return Ok(());
         }
@@ -96,11 +103,13 @@ impl PlanNodeRepository for PlanNodeRepositoryImpl {
                     node_type,
                     display_order,
                     service_id,
+                    owner_id,
+                    editor_ids,
                     created_at,
                     updated_at,
                     created_by,
                     updated_by
-                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
                 "#,
                 node.id,
                 node.scenario_id,
@@ -111,12 +120,14 @@ impl PlanNodeRepository for PlanNodeRepositoryImpl {
                 node.node_type as _,
                 node.display_order,
                 node.service_id,
+                node.owner_id,
+                &node.editor_ids,
                 node.created_at,
                 node.updated_at,
                 node.created_by,
                 node.updated_by
             )
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
         }
 
@@ -137,6 +148,8 @@ impl PlanNodeRepository for PlanNodeRepositoryImpl {
                 node_type as "node_type: _",
                 display_order,
                 service_id,
+                owner_id,
+                editor_ids,
                 created_at,
                 updated_at,
                 created_by,
@@ -144,6 +157,7 @@ impl PlanNodeRepository for PlanNodeRepositoryImpl {
                 deleted_at,
                 deleted_by
             FROM plan_nodes
+            WHERE deleted_at IS NULL
             ORDER BY created_at DESC
             LIMIT $1
             "#,
@@ -169,6 +183,8 @@ impl PlanNodeRepository for PlanNodeRepositoryImpl {
                 node_type as "node_type: _",
                 display_order,
                 service_id,
+                owner_id,
+                editor_ids,
                 created_at,
                 updated_at,
                 created_by,
@@ -200,6 +216,8 @@ impl PlanNodeRepository for PlanNodeRepositoryImpl {
                 node_type as "node_type: _",
                 display_order,
                 service_id,
+                owner_id,
+                editor_ids,
                 created_at,
                 updated_at,
                 created_by,
@@ -218,8 +236,181 @@ impl PlanNodeRepository for PlanNodeRepositoryImpl {
         Ok(recs)
     }
 
+    async fn find_tree(
+        &self,
+        scenario_id: Uuid,
+        root_id: Option<Uuid>,
+        max_depth: Option<i32>,
+    ) -> anyhow::Result<Vec<PlanNodeTreeRow>> {
+        let recs = sqlx::query_as!(
+            PlanNodeTreeRow,
+            r#"
+            WITH RECURSIVE ancestors AS (
+                -- 起点のノードからRootまで遡る
+                SELECT id, parent_id, title, 0 AS level
+                FROM plan_nodes
+                WHERE id = $2
+                UNION ALL
+                SELECT p.id, p.parent_id, p.title, a.level + 1
+                FROM plan_nodes p
+                JOIN ancestors a ON p.id = a.parent_id
+                WHERE a.level < 100
+            ),
+            root_path AS (
+                SELECT COALESCE(
+                    array_agg(title ORDER BY level DESC) FILTER (WHERE level > 0),
+                    ARRAY[]::text[]
+                ) AS path
+                FROM ancestors
+            ),
+            tree AS (
+                SELECT
+                    n.*,
+                    0 AS depth,
+                    (SELECT path FROM root_path) AS path,
+                    ARRAY[n.id] AS visited,
+                    ARRAY[n.display_order] AS sort_key
+                FROM plan_nodes n
+                WHERE n.scenario_id = $1
+                  AND n.deleted_at IS NULL
+                  AND (($2::uuid IS NULL AND n.parent_id IS NULL) OR n.id = $2)
+                UNION ALL
+                SELECT
+                    c.*,
+                    t.depth + 1,
+                    t.path || t.title,
+                    t.visited || c.id,
+                    t.sort_key || c.display_order
+                FROM plan_nodes c
+                JOIN tree t ON c.parent_id = t.id
+                WHERE c.deleted_at IS NULL
+                  AND ($3::int IS NULL OR t.depth < $3)
+                  -- 不正なデータで循環していても無限ループしないようにする
+                  AND NOT c.id = ANY(t.visited)
+            )
+            SELECT
+                t.id as "id!",
+                t.scenario_id as "scenario_id!",
+                t.parent_id,
+                t.lineage_id as "lineage_id!",
+                t.title as "title!",
+                t.description,
+                t.node_type as "node_type!: _",
+                t.display_order as "display_order!",
+                t.service_id,
+                t.owner_id,
+                t.editor_ids as "editor_ids!",
+                t.created_at as "created_at!",
+                t.updated_at as "updated_at!",
+                t.created_by as "created_by!",
+                t.updated_by as "updated_by!",
+                t.deleted_at,
+                t.deleted_by,
+                t.depth as "depth!",
+                t.path as "path!",
+                (
+                    SELECT count(*)
+                    FROM plan_nodes c
+                    WHERE c.parent_id = t.id AND c.deleted_at IS NULL
+                ) as "child_count!"
+            FROM tree t
+            ORDER BY t.depth, t.sort_key, t.created_at
+            "#,
+            scenario_id,
+            root_id,
+            max_depth
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(recs)
+    }
+
+    async fn find_with_ancestors(&self, id: Uuid) -> anyhow::Result<Vec<PlanNode>> {
+        let recs = sqlx::query_as!(
+            PlanNode,
+            r#"
+            WITH RECURSIVE ancestors AS (
+                SELECT n.*, 0 AS level
+                FROM plan_nodes n
+                WHERE n.id = $1 AND n.deleted_at IS NULL
+                UNION ALL
+                SELECT p.*, a.level + 1
+                FROM plan_nodes p
+                JOIN ancestors a ON p.id = a.parent_id
+                WHERE p.deleted_at IS NULL AND a.level < 100
+            )
+            SELECT
+                id as "id!",
+                scenario_id as "scenario_id!",
+                parent_id,
+                lineage_id as "lineage_id!",
+                title as "title!",
+                description,
+                node_type as "node_type!: _",
+                display_order as "display_order!",
+                service_id,
+                owner_id,
+                editor_ids as "editor_ids!",
+                created_at as "created_at!",
+                updated_at as "updated_at!",
+                created_by as "created_by!",
+                updated_by as "updated_by!",
+                deleted_at,
+                deleted_by
+            FROM ancestors
+            ORDER BY level
+            "#,
+            id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(recs)
+    }
+
+    async fn find_by_scenario_id_for_update(
+        &self,
+        tx: &mut PgConnection,
+        scenario_id: Uuid,
+    ) -> anyhow::Result<Vec<PlanNode>> {
+        let recs = sqlx::query_as!(
+            PlanNode,
+            r#"
+            SELECT
+                id,
+                scenario_id,
+                parent_id,
+                lineage_id,
+                title,
+                description,
+                node_type as "node_type: _",
+                display_order,
+                service_id,
+                owner_id,
+                editor_ids,
+                created_at,
+                updated_at,
+                created_by,
+                updated_by,
+                deleted_at,
+                deleted_by
+            FROM plan_nodes
+            WHERE scenario_id = $1 AND deleted_at IS NULL
+            ORDER BY display_order ASC, created_at ASC
+            FOR UPDATE
+            "#,
+            scenario_id
+        )
+        .fetch_all(tx)
+        .await?;
+
+        Ok(recs)
+    }
+
     async fn update(
         &self,
+        tx: &mut PgConnection,
         id: Uuid,
         params: UpdatePlanNodeParams,
         updated_by: Uuid,
@@ -247,48 +438,243 @@ impl PlanNodeRepository for PlanNodeRepositoryImpl {
         builder.push_bind(id);
         builder.push(" RETURNING *");
 
-        let node = builder
-            .build_query_as::<PlanNode>()
-            .fetch_one(&self.pool)
-            .await?;
+        let node = builder.build_query_as::<PlanNode>().fetch_one(tx).await?;
 
         Ok(node)
     }
 
-    async fn delete(&self, id: Uuid) -> anyhow::Result<()> {
-        // 子ノードが存在するかどうかチェック
-        let child_count: i64 =
-            sqlx::query_scalar("SELECT count(*) FROM plan_nodes WHERE parent_id = $1")
-                .bind(id)
-                .fetch_one(&self.pool)
-                .await?;
+    async fn update_assignees(
+        &self,
+        id: Uuid,
+        owner_id: Option<Uuid>,
+        editor_ids: Vec<Uuid>,
+        updated_by: Uuid,
+    ) -> anyhow::Result<PlanNode> {
+        let mut user_ids = editor_ids.clone();
+        user_ids.extend(owner_id);
+        user_ids.sort();
+        user_ids.dedup();
 
-        if child_count > 0 {
-            return Err(anyhow::anyhow!("子ノードが存在するため削除できません。"));
-        }
+        let user_count = sqlx::query_scalar!(
+            r#"SELECT count(*) as "count!" FROM users WHERE id = ANY($1)"#,
+            &user_ids
+        )
+        .fetch_one(&self.pool)
+        .await?;
 
-        // 紐づくPlEntryが存在するかどうかチェック
-        let entry_count: i64 =
-            sqlx::query_scalar("SELECT count(*) FROM pl_entries WHERE node_id = $1")
-                .bind(id)
-                .fetch_one(&self.pool)
-                .await?;
-
-        if entry_count > 0 {
-            return Err(anyhow::anyhow!(
-                "数値データが紐づいているため削除できません。"
-            ));
+        if user_count != user_ids.len() as i64 {
+            return Err(anyhow::anyhow!("Assigned user not found"));
         }
 
-        let result = sqlx::query("DELETE FROM plan_nodes WHERE id = $1")
-            .bind(id)
-            .execute(&self.pool)
-            .await?;
+        let node = sqlx::query_as!(
+            PlanNode,
+            r#"
+            UPDATE plan_nodes
+            SET
+                owner_id = $2,
+                editor_ids = $3,
+                updated_at = NOW(),
+                updated_by = $4
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING
+                id,
+                scenario_id,
+                parent_id,
+                lineage_id,
+                title,
+                description,
+                node_type as "node_type: _",
+                display_order,
+                service_id,
+                owner_id,
+                editor_ids,
+                created_at,
+                updated_at,
+                created_by,
+                updated_by,
+                deleted_at,
+                deleted_by
+            "#,
+            id,
+            owner_id,
+            &editor_ids,
+            updated_by
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("PlanNode not found"))?;
+
+        Ok(node)
+    }
+
+    async fn move_to(
+        &self,
+        tx: &mut PgConnection,
+        id: Uuid,
+        parent_id: Option<Uuid>,
+        display_order: i32,
+        updated_by: Uuid,
+    ) -> anyhow::Result<PlanNode> {
+        let rec = sqlx::query_as!(
+            PlanNode,
+            r#"
+            UPDATE plan_nodes
+            SET
+                parent_id = $2,
+                display_order = $3,
+                updated_at = NOW(),
+                updated_by = $4
+            WHERE id = $1
+            RETURNING
+                id,
+                scenario_id,
+                parent_id,
+                lineage_id,
+                title,
+                description,
+                node_type as "node_type: _",
+                display_order,
+                service_id,
+                owner_id,
+                editor_ids,
+                created_at,
+                updated_at,
+                created_by,
+                updated_by,
+                deleted_at,
+                deleted_by
+            "#,
+            id,
+            parent_id,
+            display_order,
+            updated_by
+        )
+        .fetch_optional(tx)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("PlanNode not found"))?;
+
+        Ok(rec)
+    }
 
-        if result.rows_affected() == 0 {
+    async fn delete(
+        &self,
+        tx: &mut PgConnection,
+        ids: Vec<Uuid>,
+        deleted_by: Uuid,
+    ) -> anyhow::Result<()> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE plan_nodes
+            SET
+                deleted_at = NOW(),
+                deleted_by = $2
+            WHERE id = ANY($1) AND deleted_at IS NULL
+            "#,
+            &ids,
+            deleted_by
+        )
+        .execute(tx)
+        .await?;
+
+        if result.rows_affected() != ids.len() as u64 {
             return Err(anyhow::anyhow!("Plan Node not found."));
         }
 
         Ok(())
     }
+
+    async fn find_deleted_by_id(&self, id: Uuid) -> anyhow::Result<Option<PlanNode>> {
+        let rec = sqlx::query_as!(
+            PlanNode,
+            r#"
+            SELECT
+                id,
+                scenario_id,
+                parent_id,
+                lineage_id,
+                title,
+                description,
+                node_type as "node_type: _",
+                display_order,
+                service_id,
+                owner_id,
+                editor_ids,
+                created_at,
+                updated_at,
+                created_by,
+                updated_by,
+                deleted_at,
+                deleted_by
+            FROM plan_nodes
+            WHERE id = $1 AND deleted_at IS NOT NULL
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(rec)
+    }
+
+    async fn find_deleted_by_scenario_id(
+        &self,
+        scenario_id: Uuid,
+    ) -> anyhow::Result<Vec<PlanNode>> {
+        let recs = sqlx::query_as!(
+            PlanNode,
+            r#"
+            SELECT
+                id,
+                scenario_id,
+                parent_id,
+                lineage_id,
+                title,
+                description,
+                node_type as "node_type: _",
+                display_order,
+                service_id,
+                owner_id,
+                editor_ids,
+                created_at,
+                updated_at,
+                created_by,
+                updated_by,
+                deleted_at,
+                deleted_by
+            FROM plan_nodes
+            WHERE scenario_id = $1 AND deleted_at IS NOT NULL
+            ORDER BY deleted_at DESC, display_order, created_at
+            "#,
+            scenario_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(recs)
+    }
+
+    async fn restore(
+        &self,
+        tx: &mut PgConnection,
+        ids: Vec<Uuid>,
+        restored_by: Uuid,
+    ) -> anyhow::Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE plan_nodes
+            SET
+                deleted_at = NULL,
+                deleted_by = NULL,
+                updated_at = NOW(),
+                updated_by = $2
+            WHERE id = ANY($1) AND deleted_at IS NOT NULL
+            "#,
+            &ids,
+            restored_by
+        )
+        .execute(tx)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
 }
@@ -1,7 +1,10 @@
-use crate::domain::scenarios::{Scenario, ScenarioRepository};
-use sqlx::PgPool;
+use crate::domain::scenarios::{
+    Scenario, ScenarioRepository, ScenarioStatus, ScenarioStatusTransition,
+};
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 
+#[derive(Debug, Clone)]
 pub struct ScenarioRepositoryImpl {
     pool: PgPool,
 }
@@ -14,7 +17,7 @@ impl ScenarioRepositoryImpl {
 
 #[async_trait::async_trait]
 impl ScenarioRepository for ScenarioRepositoryImpl {
-    async fn create(&self, scenario: &Scenario) -> anyhow::Result<Scenario> {
+    async fn create(&self, tx: &mut PgConnection, scenario: &Scenario) -> anyhow::Result<Scenario> {
         let rec = sqlx::query_as!(
             Scenario,
             r#"
@@ -27,14 +30,33 @@ impl ScenarioRepository for ScenarioRepositoryImpl {
                 end_date,
                 is_locked,
                 is_current,
+                kind,
+                parent_scenario_id,
+                status,
+                created_at,
+                updated_at,
+                created_by,
+                updated_by,
+                deleted_at,
+                deleted_by
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+            RETURNING
+                id,
+                name,
+                description,
+                start_date,
+                end_date,
+                is_locked,
+                is_current,
+                kind as "kind: _",
+                parent_scenario_id,
+                status as "status: _",
                 created_at,
                 updated_at,
                 created_by,
                 updated_by,
                 deleted_at,
                 deleted_by
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
-            RETURNING *
             "#,
             scenario.id,
             scenario.name,
@@ -43,6 +65,9 @@ impl ScenarioRepository for ScenarioRepositoryImpl {
             scenario.end_date,
             scenario.is_locked,
             scenario.is_current,
+            scenario.kind as _,
+            scenario.parent_scenario_id,
+            scenario.status as _,
             scenario.created_at,
             scenario.updated_at,
             scenario.created_by,
@@ -50,7 +75,7 @@ impl ScenarioRepository for ScenarioRepositoryImpl {
             scenario.deleted_at,
             scenario.deleted_by
         )
-        .fetch_one(&self.pool)
+        .fetch_one(tx)
         .await?;
 
         Ok(rec)
@@ -60,7 +85,24 @@ impl ScenarioRepository for ScenarioRepositoryImpl {
         let recs = sqlx::query_as!(
             Scenario,
             r#"
-            SELECT * FROM scenarios
+            SELECT
+                id,
+                name,
+                description,
+                start_date,
+                end_date,
+                is_locked,
+                is_current,
+                kind as "kind: _",
+                parent_scenario_id,
+                status as "status: _",
+                created_at,
+                updated_at,
+                created_by,
+                updated_by,
+                deleted_at,
+                deleted_by
+            FROM scenarios
             WHERE deleted_at IS NULL
             ORDER BY start_date DESC
             "#
@@ -72,15 +114,87 @@ impl ScenarioRepository for ScenarioRepositoryImpl {
     }
 
     async fn find_by_id(&self, id: Uuid) -> anyhow::Result<Option<Scenario>> {
-        let rec = sqlx::query_as!(Scenario, "SELECT * FROM scenarios WHERE id = $1", id)
-            .fetch_optional(&self.pool)
-            .await?;
+        let rec = sqlx::query_as!(
+            Scenario,
+            r#"
+            SELECT
+                id,
+                name,
+                description,
+                start_date,
+                end_date,
+                is_locked,
+                is_current,
+                kind as "kind: _",
+                parent_scenario_id,
+                status as "status: _",
+                created_at,
+                updated_at,
+                created_by,
+                updated_by,
+                deleted_at,
+                deleted_by
+            FROM scenarios
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
 
         Ok(rec)
     }
 
-    async fn set_current(&self, id: Uuid) -> anyhow::Result<()> {
-        let mut tx = self.pool.begin().await?;
+    async fn find_by_node_id(
+        &self,
+        tx: &mut PgConnection,
+        node_id: Uuid,
+    ) -> anyhow::Result<Option<Scenario>> {
+        let rec = sqlx::query_as!(
+            Scenario,
+            r#"
+            SELECT
+                s.id,
+                s.name,
+                s.description,
+                s.start_date,
+                s.end_date,
+                s.is_locked,
+                s.is_current,
+                s.kind as "kind: _",
+                s.parent_scenario_id,
+                s.status as "status: _",
+                s.created_at,
+                s.updated_at,
+                s.created_by,
+                s.updated_by,
+                s.deleted_at,
+                s.deleted_by
+            FROM scenarios s
+            JOIN plan_nodes n ON n.scenario_id = s.id
+            WHERE n.id = $1
+            "#,
+            node_id
+        )
+        .fetch_optional(tx)
+        .await?;
+
+        Ok(rec)
+    }
+
+    async fn set_current(&self, tx: &mut PgConnection, id: Uuid) -> anyhow::Result<()> {
+        // 締め済みのシナリオは作成中にできない
+        let is_locked = sqlx::query_scalar!(
+            "SELECT is_locked FROM scenarios WHERE id = $1 FOR UPDATE",
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Scenario not found"))?;
+
+        if is_locked {
+            return Err(anyhow::anyhow!("Locked scenarios cannot be activated"));
+        }
 
         sqlx::query("UPDATE scenarios SET is_current = FALSE")
             .execute(&mut *tx)
@@ -92,11 +206,151 @@ impl ScenarioRepository for ScenarioRepositoryImpl {
             .await?;
 
         if result.rows_affected() == 0 {
-            tx.rollback().await?;
             return Err(anyhow::anyhow!("Scenario not found"));
         }
 
-        tx.commit().await?;
         Ok(())
     }
+
+    async fn set_locked(
+        &self,
+        id: Uuid,
+        is_locked: bool,
+        user_id: Uuid,
+    ) -> anyhow::Result<Scenario> {
+        let rec = sqlx::query_as!(
+            Scenario,
+            r#"
+            UPDATE scenarios
+            SET
+                is_locked = $2,
+                updated_at = NOW(),
+                updated_by = $3
+            WHERE id = $1
+            RETURNING
+                id,
+                name,
+                description,
+                start_date,
+                end_date,
+                is_locked,
+                is_current,
+                kind as "kind: _",
+                parent_scenario_id,
+                status as "status: _",
+                created_at,
+                updated_at,
+                created_by,
+                updated_by,
+                deleted_at,
+                deleted_by
+            "#,
+            id,
+            is_locked,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Scenario not found"))?;
+
+        Ok(rec)
+    }
+
+    async fn transition_status(
+        &self,
+        transition: &ScenarioStatusTransition,
+    ) -> anyhow::Result<Scenario> {
+        let mut tx = self.pool.begin().await?;
+
+        let lock = transition.to_status == ScenarioStatus::Approved;
+        let unlock = transition.from_status == ScenarioStatus::Approved;
+        let rec = sqlx::query_as!(
+            Scenario,
+            r#"
+            UPDATE scenarios
+            SET
+                status = $3,
+                is_locked = (is_locked OR $4) AND NOT $6,
+                updated_at = NOW(),
+                updated_by = $5
+            WHERE id = $1
+              AND status = $2
+            RETURNING
+                id,
+                name,
+                description,
+                start_date,
+                end_date,
+                is_locked,
+                is_current,
+                kind as "kind: _",
+                parent_scenario_id,
+                status as "status: _",
+                created_at,
+                updated_at,
+                created_by,
+                updated_by,
+                deleted_at,
+                deleted_by
+            "#,
+            transition.scenario_id,
+            transition.from_status as _,
+            transition.to_status as _,
+            lock,
+            transition.acted_by,
+            unlock
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Scenario status has been changed by another user"))?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO scenario_status_transitions
+            (
+                id,
+                scenario_id,
+                from_status,
+                to_status,
+                comment,
+                acted_at,
+                acted_by
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            transition.id,
+            transition.scenario_id,
+            transition.from_status as _,
+            transition.to_status as _,
+            transition.comment,
+            transition.acted_at,
+            transition.acted_by
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(rec)
+    }
+
+    async fn find_status_transitions(&self) -> anyhow::Result<Vec<ScenarioStatusTransition>> {
+        let recs = sqlx::query_as!(
+            ScenarioStatusTransition,
+            r#"
+            SELECT
+                id,
+                scenario_id,
+                from_status as "from_status: _",
+                to_status as "to_status: _",
+                comment,
+                acted_at,
+                acted_by
+            FROM scenario_status_transitions
+            ORDER BY acted_at
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(recs)
+    }
 }
@@ -98,4 +98,27 @@ impl UserRepository for UserRepositoryImpl {
 
         Ok(rec)
     }
+
+    async fn find_by_ids(&self, ids: Vec<Uuid>) -> anyhow::Result<Vec<User>> {
+        let recs = sqlx::query_as!(
+            User,
+            r#"
+            SELECT
+                id,
+                name,
+                email,
+                password_hash,
+                role as "role: _",
+                created_at,
+                updated_at
+            FROM users
+            WHERE id = ANY($1)
+            "#,
+            &ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(recs)
+    }
 }
@@ -0,0 +1,165 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use rust_xlsxwriter::{Format, Workbook, Worksheet};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::domain::{
+    account_items::{AccountItem, AccountType},
+    pl_entries::EntryCategory,
+    pl_reports::{self, NodeRollup, ReportColumn, ScenarioExport},
+    plan_nodes::{self, PlanNode},
+};
+
+// 固定列: ノード / 科目コード / 科目名
+const FIXED_COLUMNS: u16 = 3;
+
+/// シナリオのツリーをインデント付きの行として、計画値・確定値のシートを持つExcelファイルを作成する
+/// 箱タイプのノードの値は子ノードの小計 (rollupの結果) をそのまま使い、ノードの行には営業利益の小計を出力する
+pub fn render_scenario_workbook(export: &ScenarioExport) -> anyhow::Result<Vec<u8>> {
+    let mut workbook = Workbook::new();
+
+    for category in [EntryCategory::Plan, EntryCategory::Result] {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name(format!("{:?}", category))?;
+        write_sheet(
+            worksheet,
+            &category,
+            &export.nodes,
+            &export.account_items,
+            &export.rollups,
+            &export.columns,
+        )?;
+    }
+
+    Ok(workbook.save_to_buffer()?)
+}
+
+fn write_sheet(
+    worksheet: &mut Worksheet,
+    category: &EntryCategory,
+    nodes: &[PlanNode],
+    account_items: &[AccountItem],
+    rollups: &[NodeRollup],
+    columns: &[ReportColumn],
+) -> anyhow::Result<()> {
+    let header = Format::new()
+        .set_bold()
+        .set_border_bottom(rust_xlsxwriter::FormatBorder::Thin);
+    let amount = Format::new().set_num_format("#,##0");
+    let subtotal = amount.clone().set_bold();
+
+    // 見出し行
+    worksheet.write_with_format(0, 0, "Node", &header)?;
+    worksheet.write_with_format(0, 1, "Code", &header)?;
+    worksheet.write_with_format(0, 2, "Account item", &header)?;
+    for (i, column) in columns.iter().enumerate() {
+        worksheet.write_with_format(0, FIXED_COLUMNS + i as u16, &column.label, &header)?;
+    }
+    let total_column = FIXED_COLUMNS + columns.len() as u16;
+    worksheet.write_with_format(0, total_column, "Total", &header)?;
+    worksheet.set_column_width(0, 40)?;
+    worksheet.set_column_width(2, 24)?;
+    worksheet.set_freeze_panes(1, FIXED_COLUMNS)?;
+
+    let column_index: HashMap<NaiveDate, usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (c.month, i))
+        .collect();
+    let rollups: HashMap<Uuid, &NodeRollup> = rollups.iter().map(|r| (r.node_id, r)).collect();
+    let account_types: HashMap<Uuid, AccountType> = account_items
+        .iter()
+        .map(|i| (i.id, i.account_type.clone()))
+        .collect();
+
+    let mut row: u32 = 1;
+    for (node, path) in plan_nodes::depth_first(nodes) {
+        let depth = (path.len() - 1) as u8;
+        let mut node_format = Format::new().set_indent(depth);
+        if !node.node_type.is_entity() {
+            node_format = node_format.set_bold();
+        }
+        worksheet.write_with_format(row, 0, &node.title, &node_format)?;
+
+        // 箱タイプのノードの行には子孫を合計した営業利益を出力する
+        if !node.node_type.is_entity()
+            && let Some(rollup) = rollups.get(&node.id)
+        {
+            let mut subtotals = vec![Decimal::ZERO; columns.len()];
+            for (month, value) in
+                pl_reports::operating_profit_by_month(rollup, category, &account_types)
+            {
+                if let Some(&i) = column_index.get(&month) {
+                    subtotals[i] += value;
+                }
+            }
+
+            worksheet.write_with_format(row, 2, "Operating profit", &node_format)?;
+            for (i, value) in subtotals.iter().enumerate() {
+                worksheet.write_with_format(
+                    row,
+                    FIXED_COLUMNS + i as u16,
+                    value.to_f64().unwrap_or_default(),
+                    &subtotal,
+                )?;
+            }
+            let total: Decimal = subtotals.iter().sum();
+            worksheet.write_with_format(
+                row,
+                total_column,
+                total.to_f64().unwrap_or_default(),
+                &subtotal,
+            )?;
+        }
+        row += 1;
+
+        // 科目ごとの月別の値 (このノードに値がある科目のみ出力する)
+        let mut values: HashMap<Uuid, Vec<Decimal>> = HashMap::new();
+        let mut present: HashSet<Uuid> = HashSet::new();
+        if let Some(rollup) = rollups.get(&node.id) {
+            for cell in &rollup.cells {
+                present.insert(cell.account_item_id);
+                let Some(&i) = column_index.get(&cell.target_month) else {
+                    continue;
+                };
+                let value = match category {
+                    EntryCategory::Plan => cell.plan,
+                    EntryCategory::Result => cell.result,
+                };
+                values
+                    .entry(cell.account_item_id)
+                    .or_insert_with(|| vec![Decimal::ZERO; columns.len()])[i] += value;
+            }
+        }
+
+        for item in account_items.iter().filter(|i| present.contains(&i.id)) {
+            let item_values = values
+                .remove(&item.id)
+                .unwrap_or_else(|| vec![Decimal::ZERO; columns.len()]);
+
+            // 科目の行はツリー列を空けて、コード・科目名の列に出力する
+            worksheet.write(row, 1, &item.code)?;
+            worksheet.write(row, 2, &item.name)?;
+            for (i, value) in item_values.iter().enumerate() {
+                worksheet.write_with_format(
+                    row,
+                    FIXED_COLUMNS + i as u16,
+                    value.to_f64().unwrap_or_default(),
+                    &amount,
+                )?;
+            }
+            let total: Decimal = item_values.iter().sum();
+            worksheet.write_with_format(
+                row,
+                total_column,
+                total.to_f64().unwrap_or_default(),
+                &amount,
+            )?;
+            row += 1;
+        }
+    }
+
+    Ok(())
+}
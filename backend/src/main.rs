@@ -1,6 +1,6 @@
 use axum::http::header::{AUTHORIZATION, CONTENT_TYPE};
 use axum::http::{HeaderValue, Method};
-use axum::routing::{delete, patch};
+use axum::routing::{delete, patch, put};
 use axum::{
     Router,
     routing::{get, post},
@@ -14,7 +14,8 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use ghost_api::{
     presentation::handlers::{
-        account_items, auth, health, pl_entries, plan_nodes, scenarios, services, users,
+        account_items, auth, closed_months, fiscal_calendar, health, ledger_mappings, pl_entries,
+        pl_reports, plan_nodes, scenario_merges, scenario_snapshots, scenarios, services, users,
     },
     state::AppState,
 };
@@ -63,23 +64,108 @@ async fn main() -> anyhow::Result<()> {
         .route("/users/me", get(users::get_me))
         .route("/account-items", get(account_items::list))
         .route("/account-items", post(account_items::create))
+        .route("/fiscal-calendar", get(fiscal_calendar::get))
+        .route("/fiscal-calendar", put(fiscal_calendar::update))
+        .route(
+            "/ledger-mappings/accounts",
+            get(ledger_mappings::list_accounts),
+        )
+        .route(
+            "/ledger-mappings/accounts",
+            put(ledger_mappings::save_account),
+        )
+        .route(
+            "/ledger-mappings/accounts/{id}",
+            delete(ledger_mappings::delete_account),
+        )
+        .route(
+            "/ledger-mappings/departments",
+            get(ledger_mappings::list_departments),
+        )
+        .route(
+            "/ledger-mappings/departments",
+            put(ledger_mappings::save_department),
+        )
+        .route(
+            "/ledger-mappings/departments/{id}",
+            delete(ledger_mappings::delete_department),
+        )
         .route("/scenarios", get(scenarios::list))
         .route("/scenarios", post(scenarios::create))
+        .route("/scenarios/compare", get(pl_reports::compare))
+        .route("/scenarios/import", post(scenario_snapshots::import))
         .route("/scenarios/{id}/activate", post(scenarios::activate))
+        .route("/scenarios/{id}/lock", post(scenarios::lock))
+        .route("/scenarios/{id}/unlock", post(scenarios::unlock))
+        .route("/scenarios/{id}/submit", post(scenarios::submit))
+        .route("/scenarios/{id}/approve", post(scenarios::approve))
+        .route("/scenarios/{id}/reject", post(scenarios::reject))
         .route("/scenarios/{id}/rollover", post(scenarios::rollover))
+        .route("/scenarios/{id}/branch", post(scenarios::branch))
+        .route("/scenarios/{id}/merge", get(scenario_merges::merge_preview))
+        .route("/scenarios/{id}/merge", post(scenario_merges::merge))
+        .route(
+            "/scenarios/{id}/compare-parent",
+            get(pl_reports::compare_with_parent),
+        )
+        .route("/scenarios/{id}/closed-months", get(closed_months::list))
+        .route("/scenarios/{id}/closed-months", post(closed_months::close))
+        .route(
+            "/scenarios/{id}/closed-months/reopen",
+            post(closed_months::reopen),
+        )
+        .route(
+            "/scenarios/{id}/closed-months/events",
+            get(closed_months::list_events),
+        )
         .route(
             "/scenarios/{id}/pl-entries",
             get(pl_entries::list_by_scenario),
         )
+        .route(
+            "/scenarios/{id}/pl-entries/import",
+            post(pl_entries::import_csv),
+        )
+        .route(
+            "/scenarios/{id}/pl-entries/import-ledger",
+            post(pl_entries::import_ledger),
+        )
+        .route("/scenarios/{id}/pl-rollup", get(pl_reports::rollup))
+        .route(
+            "/scenarios/{id}/pl-statement",
+            get(pl_reports::pl_statement),
+        )
+        .route("/scenarios/{id}/pl-variance", get(pl_reports::variance))
+        .route("/scenarios/{id}/service-pl", get(pl_reports::service_pl))
+        .route("/scenarios/{id}/pl-forecast", get(pl_reports::forecast))
+        .route("/scenarios/{id}/pl-grid", get(pl_reports::grid))
+        .route("/scenarios/{id}/export/xlsx", get(pl_reports::export_xlsx))
+        .route(
+            "/scenarios/{id}/export/snapshot",
+            get(scenario_snapshots::export),
+        )
         .route("/services", get(services::list))
         .route("/services", post(services::create))
         .route("/plan-nodes", get(plan_nodes::list))
         .route("/plan-nodes", post(plan_nodes::create))
+        .route("/plan-nodes/tree", get(plan_nodes::tree))
+        .route("/plan-nodes/trash", get(plan_nodes::trash))
         .route("/plan-nodes/{id}", patch(plan_nodes::update))
         .route("/plan-nodes/{id}", delete(plan_nodes::delete))
+        .route("/plan-nodes/{id}/move", post(plan_nodes::move_node))
+        .route("/plan-nodes/{id}/copy", post(plan_nodes::copy))
+        .route("/plan-nodes/{id}/restore", post(plan_nodes::restore))
+        .route(
+            "/plan-nodes/{id}/assignees",
+            put(plan_nodes::update_assignees),
+        )
         .route("/pl-entries", get(pl_entries::list))
         .route("/pl-entries", post(pl_entries::save))
         .route("/pl-entries/bulk", post(pl_entries::bulk_save))
+        .route(
+            "/pl-entries/invalid-months",
+            get(pl_entries::list_invalid_months),
+        )
         .layer(cors)
         .with_state(state);
 
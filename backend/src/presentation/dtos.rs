@@ -1,12 +1,16 @@
 use chrono::NaiveDate;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 use validator::Validate;
 
 use crate::domain::plan_nodes::UpdatePlanNodeParams;
+use crate::domain::rollover::{RolloverEntryMode, RolloverOptions};
+use crate::domain::subtree_copies::SubtreeCopyOptions;
 use crate::domain::{
-    account_items::AccountType, pl_entries::EntryCategory, plan_nodes::NodeType, user::UserRole,
+    account_items::AccountType, fiscal_calendar::PeriodGranularity, pl_entries::EntryCategory,
+    plan_nodes::NodeType, scenarios::ScenarioKind, user::UserRole,
 };
 
 #[derive(Debug, Deserialize, Validate)]
@@ -76,6 +80,26 @@ pub struct CreateScenarioRequest {
     pub description: Option<String>,
     pub start_date: NaiveDate,
     pub end_date: NaiveDate,
+
+    #[serde(default)]
+    pub kind: ScenarioKind,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ScenarioStatusRequest {
+    // 差し戻しの場合は必須
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct BranchScenarioRequest {
+    #[validate(length(min = 1, message = "Name is required"))]
+    pub name: String,
+
+    pub description: Option<String>,
+
+    // 省略時はWhatIf
+    pub kind: Option<ScenarioKind>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -96,6 +120,43 @@ pub struct CreatePlanNodeRequest {
     pub service_id: Option<Uuid>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct MovePlanNodeRequest {
+    // Rootに移動する場合はnull
+    pub parent_id: Option<Uuid>,
+
+    // 省略時は現在の並び順のまま
+    pub display_order: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CopyPlanNodeRequest {
+    // Rootにコピーする場合はnull
+    pub parent_id: Option<Uuid>,
+
+    // 省略時は元のノードと同じ
+    pub title: Option<String>,
+    pub display_order: Option<i32>,
+
+    // 計画値もコピーする (確定値はコピーしない)
+    #[serde(default)]
+    pub copy_entries: bool,
+    // 計画値の対象月をずらす月数 (負数の場合は前にずらす)
+    #[serde(default)]
+    pub month_offset: i32,
+}
+
+impl CopyPlanNodeRequest {
+    pub fn options(&self) -> SubtreeCopyOptions {
+        SubtreeCopyOptions {
+            title: self.title.clone(),
+            display_order: self.display_order,
+            copy_entries: self.copy_entries,
+            month_offset: self.month_offset,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct UpdatePlanNodeRequest {
     pub title: Option<String>,
@@ -118,6 +179,37 @@ pub struct ListPlanNodesQuery {
     pub scenario_id: Option<Uuid>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct UpdatePlanNodeAssigneesRequest {
+    // nullの場合は親ノードの担当者を引き継ぐ
+    pub owner_id: Option<Uuid>,
+    #[serde(default)]
+    pub editor_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeletePlanNodeQuery {
+    // 子孫ノードごと削除する
+    #[serde(default)]
+    pub cascade: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PlanNodeTrashQuery {
+    pub scenario_id: Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PlanNodeTreeQuery {
+    pub scenario_id: Uuid,
+
+    // 省略時はシナリオ全体
+    pub root_id: Option<Uuid>,
+
+    // 起点のノードからの深さ (0は起点のノードのみ)。省略時は末端まで
+    pub max_depth: Option<u32>,
+}
+
 #[derive(Debug, Deserialize, Validate)]
 pub struct SavePlEntryRequest {
     pub node_id: Uuid,
@@ -134,6 +226,44 @@ pub struct BulkSavePlEntryRequest {
     pub entries: Vec<SavePlEntryRequest>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ImportPlEntriesQuery {
+    // trueの場合は検証のみ行い、書き込まない
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+// CSVの1行
+// ノードはlineage_idか、ルートからのタイトルを"/"で繋いだnode_pathのどちらかで指定する
+#[derive(Debug, Deserialize)]
+pub struct PlEntryCsvRow {
+    #[serde(default)]
+    pub lineage_id: Option<String>,
+    #[serde(default)]
+    pub node_path: Option<String>,
+    pub account_item_code: String,
+    pub target_month: String, // YYYY-MM or YYYY-MM-01
+    pub entry_category: String,
+    pub amount: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlEntryImportError {
+    pub row: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlEntryImportReport {
+    pub dry_run: bool,
+    pub total_rows: usize,
+    pub valid_rows: usize,
+    pub imported_rows: usize,
+    pub errors: Vec<PlEntryImportError>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ListPlEntryQuery {
     pub node_id: Uuid,
@@ -146,4 +276,186 @@ pub struct RolloverScenarioRequest {
     pub name: String,
     pub start_date: NaiveDate,
     pub end_date: NaiveDate,
+
+    // 旧シナリオとのstart_dateの差だけEntryの対象月をずらす
+    #[serde(default)]
+    pub shift_months: bool,
+    #[serde(default)]
+    pub entry_mode: RolloverEntryMode,
+    // 終了したサービスに紐づくノードをコピーしない
+    #[serde(default)]
+    pub drop_closed: bool,
+    // 科目タイプごとの増減率 (%) 例: {"Revenue": 10}
+    #[serde(default)]
+    pub uplift: HashMap<AccountType, Decimal>,
+}
+
+impl RolloverScenarioRequest {
+    pub fn options(&self) -> RolloverOptions {
+        RolloverOptions {
+            shift_months: self.shift_months,
+            entry_mode: self.entry_mode,
+            drop_closed: self.drop_closed,
+            uplift: self.uplift.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MergeBranchRequest {
+    // 取り込む差分のキー (GET /scenarios/{id}/merge のkey)
+    pub keys: Vec<String>,
+
+    // trueの場合は分岐元でも変更されている差分も上書きで取り込む
+    #[serde(default)]
+    pub overwrite_conflicts: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RolloverScenarioQuery {
+    // trueの場合は作成されるノード・Entryの見込みだけを返し、書き込まない
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PlRollupQuery {
+    // 指定した場合は会計期間 (四半期・半期・年度) 単位で集計する
+    pub period: Option<PeriodGranularity>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PlStatementQuery {
+    pub entry_category: EntryCategory,
+    pub period: Option<PeriodGranularity>,
+
+    // 指定した場合はそのノード配下のみを集計する
+    pub node_id: Option<Uuid>,
+
+    // 指定した場合はそのサービスに紐づくノードのみを集計する
+    pub service_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PlVarianceQuery {
+    // 集計対象の月の範囲 (YYYY-MM-01)
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+
+    pub service_id: Option<Uuid>,
+
+    // 指定した場合はそのノード配下のみを返す
+    pub node_id: Option<Uuid>,
+
+    pub period: Option<PeriodGranularity>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompareScenariosQuery {
+    // 比較元 (例: 期初計画)
+    pub base_id: Uuid,
+    // 比較先 (例: 修正計画)
+    pub target_id: Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ServicePlQuery {
+    // 指定した場合はそのサービスのみを返す
+    pub service_id: Option<Uuid>,
+
+    pub period: Option<PeriodGranularity>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PlForecastQuery {
+    // この月 (YYYY-MM-01) より前は確定値、この月以降は計画値を使う
+    pub cutoff: NaiveDate,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportScenarioQuery {
+    pub period: Option<PeriodGranularity>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PlGridQuery {
+    pub entry_category: EntryCategory,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateFiscalCalendarRequest {
+    #[validate(range(min = 1, max = 12, message = "Month must be between 1 and 12"))]
+    pub fiscal_year_start_month: i32,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SaveLedgerAccountMappingRequest {
+    #[validate(length(min = 1, message = "External code is required"))]
+    pub external_code: String,
+    // 未指定の場合はこのコードの行を取り込み対象外にする
+    pub account_item_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SaveLedgerDepartmentMappingRequest {
+    #[validate(length(min = 1, message = "External code is required"))]
+    pub external_code: String,
+    // どちらか一方を指定する
+    pub lineage_id: Option<Uuid>,
+    pub service_id: Option<Uuid>,
+}
+
+// 会計システムの仕訳帳・試算表CSVの1行
+// amountがある場合はそのまま、ない場合は科目タイプに応じて借方・貸方の差額を金額とする
+#[derive(Debug, Deserialize)]
+pub struct LedgerCsvRow {
+    #[serde(alias = "month")]
+    pub date: String, // YYYY-MM-DD or YYYY-MM
+    pub account_code: String,
+    #[serde(default)]
+    pub department: Option<String>,
+    #[serde(default)]
+    pub debit: Option<String>,
+    #[serde(default)]
+    pub credit: Option<String>,
+    #[serde(default)]
+    pub amount: Option<String>,
+}
+
+// 月ごとに合算した取り込み結果の1セル
+#[derive(Debug, Serialize)]
+pub struct LedgerImportCell {
+    pub node_id: Uuid,
+    pub account_item_id: Uuid,
+    pub target_month: NaiveDate,
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LedgerImportReport {
+    pub dry_run: bool,
+    pub total_rows: usize,
+    pub valid_rows: usize,
+    pub skipped_rows: usize, // 取り込み対象外の勘定科目の行
+    pub imported_entries: usize,
+    pub entries: Vec<LedgerImportCell>,
+    pub errors: Vec<PlEntryImportError>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportScenarioSnapshotQuery {
+    // 指定した場合はアーカイブのシナリオ名の代わりに使う
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CloseMonthRequest {
+    pub target_month: NaiveDate, // YYYY-MM-01
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ReopenMonthRequest {
+    pub target_month: NaiveDate, // YYYY-MM-01
+    #[validate(length(min = 1, message = "Reason is required"))]
+    pub reason: String,
 }
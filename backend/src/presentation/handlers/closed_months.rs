@@ -0,0 +1,118 @@
+use axum::extract::Path;
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    application::services::closed_months::ClosedMonthService,
+    domain::user::UserRole,
+    infrastructure::persistence::{
+        closed_months::ClosedMonthRepositoryImpl, scenarios::ScenarioRepositoryImpl,
+    },
+    presentation::{
+        dtos::{CloseMonthRequest, ReopenMonthRequest},
+        extractors::AuthUser,
+    },
+    state::AppState,
+};
+
+fn closing_error(context: &str, e: anyhow::Error) -> (StatusCode, String) {
+    let msg = e.to_string();
+    if msg.contains("not found") {
+        (StatusCode::NOT_FOUND, msg)
+    } else if msg.contains("Read-Only") {
+        (StatusCode::FORBIDDEN, msg)
+    } else if msg.contains("must be") {
+        (StatusCode::BAD_REQUEST, msg)
+    } else if msg.contains("already closed") || msg.contains("not closed") {
+        (StatusCode::CONFLICT, msg)
+    } else {
+        tracing::error!("{} error: {:?}", context, e);
+        (StatusCode::INTERNAL_SERVER_ERROR, msg)
+    }
+}
+
+pub async fn list(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+    Path(scenario_id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let closed_month_repo = ClosedMonthRepositoryImpl::new(state.pool.clone());
+    let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
+    let service = ClosedMonthService::new(closed_month_repo, scenario_repo);
+
+    match service.list(scenario_id).await {
+        Ok(months) => Ok((StatusCode::OK, Json(months))),
+        Err(e) => Err(closing_error("List closed months", e)),
+    }
+}
+
+pub async fn close(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(scenario_id): Path<Uuid>,
+    Json(payload): Json<CloseMonthRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    if auth_user.role != UserRole::Admin {
+        return Err((StatusCode::FORBIDDEN, "Permission denied".to_string()));
+    }
+
+    let closed_month_repo = ClosedMonthRepositoryImpl::new(state.pool.clone());
+    let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
+    let service = ClosedMonthService::new(closed_month_repo, scenario_repo);
+
+    match service
+        .close(scenario_id, payload.target_month, auth_user.id)
+        .await
+    {
+        Ok(month) => Ok((StatusCode::CREATED, Json(month))),
+        Err(e) => Err(closing_error("Close month", e)),
+    }
+}
+
+pub async fn reopen(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(scenario_id): Path<Uuid>,
+    Json(payload): Json<ReopenMonthRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    if auth_user.role != UserRole::Admin {
+        return Err((StatusCode::FORBIDDEN, "Permission denied".to_string()));
+    }
+
+    if let Err(e) = payload.validate() {
+        return Err((StatusCode::BAD_REQUEST, format!("Validation error: {}", e)));
+    }
+
+    let closed_month_repo = ClosedMonthRepositoryImpl::new(state.pool.clone());
+    let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
+    let service = ClosedMonthService::new(closed_month_repo, scenario_repo);
+
+    match service
+        .reopen(
+            scenario_id,
+            payload.target_month,
+            payload.reason,
+            auth_user.id,
+        )
+        .await
+    {
+        Ok(event) => Ok((StatusCode::OK, Json(event))),
+        Err(e) => Err(closing_error("Reopen month", e)),
+    }
+}
+
+pub async fn list_events(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+    Path(scenario_id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let closed_month_repo = ClosedMonthRepositoryImpl::new(state.pool.clone());
+    let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
+    let service = ClosedMonthService::new(closed_month_repo, scenario_repo);
+
+    match service.list_events(scenario_id).await {
+        Ok(events) => Ok((StatusCode::OK, Json(events))),
+        Err(e) => Err(closing_error("List closing events", e)),
+    }
+}
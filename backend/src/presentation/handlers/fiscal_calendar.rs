@@ -0,0 +1,56 @@
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use validator::Validate;
+
+use crate::{
+    application::services::fiscal_calendar::FiscalCalendarService,
+    domain::user::UserRole,
+    infrastructure::persistence::fiscal_calendar::FiscalCalendarRepositoryImpl,
+    presentation::{dtos::UpdateFiscalCalendarRequest, extractors::AuthUser},
+    state::AppState,
+};
+
+pub async fn get(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let repo = FiscalCalendarRepositoryImpl::new(state.pool);
+    let service = FiscalCalendarService::new(repo);
+
+    match service.get().await {
+        Ok(calendar) => Ok((StatusCode::OK, Json(calendar))),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+pub async fn update(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<UpdateFiscalCalendarRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    if auth_user.role != UserRole::Admin {
+        return Err((StatusCode::FORBIDDEN, "Permission denied".to_string()));
+    }
+
+    if let Err(e) = payload.validate() {
+        return Err((StatusCode::BAD_REQUEST, format!("Validation error: {}", e)));
+    }
+
+    let repo = FiscalCalendarRepositoryImpl::new(state.pool);
+    let service = FiscalCalendarService::new(repo);
+
+    match service
+        .update(payload.fiscal_year_start_month, auth_user.id)
+        .await
+    {
+        Ok(calendar) => Ok((StatusCode::OK, Json(calendar))),
+        Err(e) => {
+            let msg = e.to_string();
+            if msg.contains("must be between") {
+                Err((StatusCode::BAD_REQUEST, msg))
+            } else {
+                tracing::error!("Update fiscal calendar error: {}", e);
+                Err((StatusCode::INTERNAL_SERVER_ERROR, msg))
+            }
+        }
+    }
+}
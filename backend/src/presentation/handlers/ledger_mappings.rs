@@ -0,0 +1,154 @@
+use axum::extract::Path;
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    application::services::ledger_mappings::LedgerMappingService,
+    domain::user::UserRole,
+    infrastructure::persistence::ledger_mappings::LedgerMappingRepositoryImpl,
+    presentation::{
+        dtos::{SaveLedgerAccountMappingRequest, SaveLedgerDepartmentMappingRequest},
+        extractors::AuthUser,
+    },
+    state::AppState,
+};
+
+fn mapping_error(context: &str, e: anyhow::Error) -> (StatusCode, String) {
+    let msg = e.to_string();
+    if msg.contains("not found") {
+        (StatusCode::NOT_FOUND, msg)
+    } else if msg.contains("must be set") || msg.contains("cannot be empty") {
+        (StatusCode::BAD_REQUEST, msg)
+    } else if msg.contains("foreign key") {
+        (
+            StatusCode::BAD_REQUEST,
+            "Referenced account item or service does not exist".to_string(),
+        )
+    } else {
+        tracing::error!("{} error: {}", context, e);
+        (StatusCode::INTERNAL_SERVER_ERROR, msg)
+    }
+}
+
+pub async fn list_accounts(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let repo = LedgerMappingRepositoryImpl::new(state.pool);
+    let service = LedgerMappingService::new(repo);
+
+    match service.list_accounts().await {
+        Ok(mappings) => Ok((StatusCode::OK, Json(mappings))),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+pub async fn save_account(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<SaveLedgerAccountMappingRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    // 権限チェック
+    if auth_user.role != UserRole::Admin {
+        return Err((StatusCode::FORBIDDEN, "Permission denied".to_string()));
+    }
+
+    // バリデーション
+    if let Err(e) = payload.validate() {
+        return Err((StatusCode::BAD_REQUEST, format!("Validation error: {}", e)));
+    }
+
+    let repo = LedgerMappingRepositoryImpl::new(state.pool);
+    let service = LedgerMappingService::new(repo);
+
+    match service
+        .save_account(payload.external_code, payload.account_item_id)
+        .await
+    {
+        Ok(mapping) => Ok((StatusCode::OK, Json(mapping))),
+        Err(e) => Err(mapping_error("Save account mapping", e)),
+    }
+}
+
+pub async fn delete_account(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    // 権限チェック
+    if auth_user.role != UserRole::Admin {
+        return Err((StatusCode::FORBIDDEN, "Permission denied".to_string()));
+    }
+
+    let repo = LedgerMappingRepositoryImpl::new(state.pool);
+    let service = LedgerMappingService::new(repo);
+
+    match service.delete_account(id).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(mapping_error("Delete account mapping", e)),
+    }
+}
+
+pub async fn list_departments(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let repo = LedgerMappingRepositoryImpl::new(state.pool);
+    let service = LedgerMappingService::new(repo);
+
+    match service.list_departments().await {
+        Ok(mappings) => Ok((StatusCode::OK, Json(mappings))),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+pub async fn save_department(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<SaveLedgerDepartmentMappingRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    // 権限チェック
+    if auth_user.role != UserRole::Admin {
+        return Err((StatusCode::FORBIDDEN, "Permission denied".to_string()));
+    }
+
+    // バリデーション
+    if let Err(e) = payload.validate() {
+        return Err((StatusCode::BAD_REQUEST, format!("Validation error: {}", e)));
+    }
+
+    let repo = LedgerMappingRepositoryImpl::new(state.pool);
+    let service = LedgerMappingService::new(repo);
+
+    match service
+        .save_department(
+            payload.external_code,
+            payload.lineage_id,
+            payload.service_id,
+        )
+        .await
+    {
+        Ok(mapping) => Ok((StatusCode::OK, Json(mapping))),
+        Err(e) => Err(mapping_error("Save department mapping", e)),
+    }
+}
+
+pub async fn delete_department(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    // 権限チェック
+    if auth_user.role != UserRole::Admin {
+        return Err((StatusCode::FORBIDDEN, "Permission denied".to_string()));
+    }
+
+    let repo = LedgerMappingRepositoryImpl::new(state.pool);
+    let service = LedgerMappingService::new(repo);
+
+    match service.delete_department(id).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(mapping_error("Delete department mapping", e)),
+    }
+}
@@ -11,12 +11,16 @@ use validator::Validate;
 use crate::infrastructure::persistence::scenarios::ScenarioRepositoryImpl;
 use crate::{
     application::services::pl_entries::PlEntryService,
+    domain::{closed_months::MonthClosedError, user::UserRole},
     infrastructure::persistence::{
-        history::PlEntryHistoryRepositoryImpl, pl_entries::PlEntryRepositoryImpl,
-        plan_nodes::PlanNodeRepositoryImpl,
+        account_item::AccountItemRepositoryImpl, closed_months::ClosedMonthRepositoryImpl,
+        history::PlEntryHistoryRepositoryImpl, ledger_mappings::LedgerMappingRepositoryImpl,
+        pl_entries::PlEntryRepositoryImpl, plan_nodes::PlanNodeRepositoryImpl,
     },
     presentation::{
-        dtos::{BulkSavePlEntryRequest, ListPlEntryQuery, SavePlEntryRequest},
+        dtos::{
+            BulkSavePlEntryRequest, ImportPlEntriesQuery, ListPlEntryQuery, SavePlEntryRequest,
+        },
         extractors::AuthUser,
     },
     state::AppState,
@@ -35,6 +39,9 @@ pub async fn save(
     let node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
     let history_repo = PlEntryHistoryRepositoryImpl::new(state.pool.clone());
     let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
+    let account_item_repo = AccountItemRepositoryImpl::new(state.pool.clone());
+    let ledger_mapping_repo = LedgerMappingRepositoryImpl::new(state.pool.clone());
+    let closed_month_repo = ClosedMonthRepositoryImpl::new(state.pool.clone());
 
     let service = PlEntryService::new(
         state.pool.clone(),
@@ -42,6 +49,9 @@ pub async fn save(
         node_repo,
         history_repo,
         scenario_repo,
+        account_item_repo,
+        ledger_mapping_repo,
+        closed_month_repo,
     );
 
     match service
@@ -53,13 +63,22 @@ pub async fn save(
             payload.amount,
             payload.description,
             auth_user.id,
+            &auth_user.role,
         )
         .await
     {
         Ok(entry) => Ok((StatusCode::OK, Json(entry))),
         Err(e) => {
             let msg = e.to_string();
-            if msg.contains("Cannot input entries") || msg.contains("Node not found") {
+            if e.is::<MonthClosedError>() {
+                Err((StatusCode::CONFLICT, msg))
+            } else if msg.contains("Read-Only") {
+                Err((StatusCode::FORBIDDEN, msg))
+            } else if msg.contains("Cannot input entries")
+                || msg.contains("Node not found")
+                || msg.contains("first day of a month")
+                || msg.contains("outside the scenario period")
+            {
                 Err((StatusCode::BAD_REQUEST, msg))
             } else {
                 tracing::error!("Save entry error: {}", e);
@@ -82,6 +101,9 @@ pub async fn bulk_save(
     let node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
     let history_repo = PlEntryHistoryRepositoryImpl::new(state.pool.clone());
     let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
+    let account_item_repo = AccountItemRepositoryImpl::new(state.pool.clone());
+    let ledger_mapping_repo = LedgerMappingRepositoryImpl::new(state.pool.clone());
+    let closed_month_repo = ClosedMonthRepositoryImpl::new(state.pool.clone());
 
     let service = PlEntryService::new(
         state.pool.clone(),
@@ -89,15 +111,26 @@ pub async fn bulk_save(
         node_repo,
         history_repo,
         scenario_repo,
+        account_item_repo,
+        ledger_mapping_repo,
+        closed_month_repo,
     );
 
-    match service.save_bulk(payload.entries, auth_user.id).await {
+    match service
+        .save_bulk(payload.entries, auth_user.id, &auth_user.role)
+        .await
+    {
         Ok(_) => Ok((StatusCode::OK, "Bulk save successful")),
         Err(e) => {
             let msg = e.to_string();
-            if msg.contains("Read-Only") {
+            if e.is::<MonthClosedError>() {
+                Err((StatusCode::CONFLICT, msg))
+            } else if msg.contains("Read-Only") {
                 Err((StatusCode::FORBIDDEN, msg))
-            } else if msg.contains("Cannot input entries") {
+            } else if msg.contains("Cannot input entries")
+                || msg.contains("first day of a month")
+                || msg.contains("outside the scenario period")
+            {
                 Err((StatusCode::BAD_REQUEST, msg))
             } else {
                 tracing::error!("Bulk save error: {}", e);
@@ -116,6 +149,9 @@ pub async fn list(
     let node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
     let history_repo = PlEntryHistoryRepositoryImpl::new(state.pool.clone());
     let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
+    let account_item_repo = AccountItemRepositoryImpl::new(state.pool.clone());
+    let ledger_mapping_repo = LedgerMappingRepositoryImpl::new(state.pool.clone());
+    let closed_month_repo = ClosedMonthRepositoryImpl::new(state.pool.clone());
 
     let service = PlEntryService::new(
         state.pool.clone(),
@@ -123,6 +159,9 @@ pub async fn list(
         node_repo,
         history_repo,
         scenario_repo,
+        account_item_repo,
+        ledger_mapping_repo,
+        closed_month_repo,
     );
 
     match service
@@ -143,6 +182,9 @@ pub async fn list_by_scenario(
     let node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
     let history_repo = PlEntryHistoryRepositoryImpl::new(state.pool.clone());
     let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
+    let account_item_repo = AccountItemRepositoryImpl::new(state.pool.clone());
+    let ledger_mapping_repo = LedgerMappingRepositoryImpl::new(state.pool.clone());
+    let closed_month_repo = ClosedMonthRepositoryImpl::new(state.pool.clone());
 
     let service = PlEntryService::new(
         state.pool.clone(),
@@ -150,6 +192,9 @@ pub async fn list_by_scenario(
         node_repo,
         history_repo,
         scenario_repo,
+        account_item_repo,
+        ledger_mapping_repo,
+        closed_month_repo,
     );
 
     match service.list_by_scenario(scenario_id).await {
@@ -160,3 +205,143 @@ pub async fn list_by_scenario(
         }
     }
 }
+
+pub async fn import_csv(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(scenario_id): Path<Uuid>,
+    Query(query): Query<ImportPlEntriesQuery>,
+    body: String,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let entry_repo = PlEntryRepositoryImpl::new(state.pool.clone());
+    let node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
+    let history_repo = PlEntryHistoryRepositoryImpl::new(state.pool.clone());
+    let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
+    let account_item_repo = AccountItemRepositoryImpl::new(state.pool.clone());
+    let ledger_mapping_repo = LedgerMappingRepositoryImpl::new(state.pool.clone());
+    let closed_month_repo = ClosedMonthRepositoryImpl::new(state.pool.clone());
+
+    let service = PlEntryService::new(
+        state.pool.clone(),
+        entry_repo,
+        node_repo,
+        history_repo,
+        scenario_repo,
+        account_item_repo,
+        ledger_mapping_repo,
+        closed_month_repo,
+    );
+
+    match service
+        .import_csv(
+            scenario_id,
+            &body,
+            query.dry_run,
+            auth_user.id,
+            &auth_user.role,
+        )
+        .await
+    {
+        Ok(report) if report.errors.is_empty() || report.dry_run => {
+            Ok((StatusCode::OK, Json(report)))
+        }
+        Ok(report) => Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(report))),
+        Err(e) => {
+            let msg = e.to_string();
+            if msg.contains("not found") {
+                Err((StatusCode::NOT_FOUND, msg))
+            } else {
+                tracing::error!("CSV import error: {}", e);
+                Err((StatusCode::INTERNAL_SERVER_ERROR, msg))
+            }
+        }
+    }
+}
+
+pub async fn import_ledger(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(scenario_id): Path<Uuid>,
+    Query(query): Query<ImportPlEntriesQuery>,
+    body: String,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let entry_repo = PlEntryRepositoryImpl::new(state.pool.clone());
+    let node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
+    let history_repo = PlEntryHistoryRepositoryImpl::new(state.pool.clone());
+    let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
+    let account_item_repo = AccountItemRepositoryImpl::new(state.pool.clone());
+    let ledger_mapping_repo = LedgerMappingRepositoryImpl::new(state.pool.clone());
+    let closed_month_repo = ClosedMonthRepositoryImpl::new(state.pool.clone());
+
+    let service = PlEntryService::new(
+        state.pool.clone(),
+        entry_repo,
+        node_repo,
+        history_repo,
+        scenario_repo,
+        account_item_repo,
+        ledger_mapping_repo,
+        closed_month_repo,
+    );
+
+    match service
+        .import_ledger(
+            scenario_id,
+            &body,
+            query.dry_run,
+            auth_user.id,
+            &auth_user.role,
+        )
+        .await
+    {
+        Ok(report) if report.errors.is_empty() || report.dry_run => {
+            Ok((StatusCode::OK, Json(report)))
+        }
+        Ok(report) => Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(report))),
+        Err(e) => {
+            let msg = e.to_string();
+            if msg.contains("not found") {
+                Err((StatusCode::NOT_FOUND, msg))
+            } else {
+                tracing::error!("Ledger import error: {}", e);
+                Err((StatusCode::INTERNAL_SERVER_ERROR, msg))
+            }
+        }
+    }
+}
+
+pub async fn list_invalid_months(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    if auth_user.role != UserRole::Admin {
+        return Err((StatusCode::FORBIDDEN, "Permission denied".to_string()));
+    }
+
+    let entry_repo = PlEntryRepositoryImpl::new(state.pool.clone());
+    let node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
+    let history_repo = PlEntryHistoryRepositoryImpl::new(state.pool.clone());
+    let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
+    let account_item_repo = AccountItemRepositoryImpl::new(state.pool.clone());
+    let ledger_mapping_repo = LedgerMappingRepositoryImpl::new(state.pool.clone());
+    let closed_month_repo = ClosedMonthRepositoryImpl::new(state.pool.clone());
+
+    let service = PlEntryService::new(
+        state.pool.clone(),
+        entry_repo,
+        node_repo,
+        history_repo,
+        scenario_repo,
+        account_item_repo,
+        ledger_mapping_repo,
+        closed_month_repo,
+    );
+
+    match service.list_invalid_months().await {
+        Ok(entries) => Ok((StatusCode::OK, Json(entries))),
+        Err(e) => {
+            tracing::error!("Invalid month report error: {:?}", e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+        }
+    }
+}
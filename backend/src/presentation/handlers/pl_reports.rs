@@ -0,0 +1,229 @@
+use axum::extract::Path;
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    application::services::pl_reports::PlReportService,
+    infrastructure::persistence::{
+        account_item::AccountItemRepositoryImpl, fiscal_calendar::FiscalCalendarRepositoryImpl,
+        pl_entries::PlEntryRepositoryImpl, plan_nodes::PlanNodeRepositoryImpl,
+        scenarios::ScenarioRepositoryImpl, services::ServiceRepositoryImpl,
+    },
+    infrastructure::xlsx,
+    presentation::{
+        dtos::{
+            CompareScenariosQuery, ExportScenarioQuery, PlForecastQuery, PlGridQuery,
+            PlRollupQuery, PlStatementQuery, PlVarianceQuery, ServicePlQuery,
+        },
+        extractors::AuthUser,
+    },
+    state::AppState,
+};
+
+type ReportService = PlReportService<
+    ScenarioRepositoryImpl,
+    PlanNodeRepositoryImpl,
+    PlEntryRepositoryImpl,
+    AccountItemRepositoryImpl,
+    ServiceRepositoryImpl,
+    FiscalCalendarRepositoryImpl,
+>;
+
+fn report_service(state: &AppState) -> ReportService {
+    let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
+    let node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
+    let entry_repo = PlEntryRepositoryImpl::new(state.pool.clone());
+    let account_item_repo = AccountItemRepositoryImpl::new(state.pool.clone());
+    let service_repo = ServiceRepositoryImpl::new(state.pool.clone());
+    let fiscal_calendar_repo = FiscalCalendarRepositoryImpl::new(state.pool.clone());
+
+    PlReportService::new(
+        scenario_repo,
+        node_repo,
+        entry_repo,
+        account_item_repo,
+        service_repo,
+        fiscal_calendar_repo,
+    )
+}
+
+fn report_error(context: &str, e: anyhow::Error) -> (StatusCode, String) {
+    let msg = e.to_string();
+    if msg.contains("not found") {
+        (StatusCode::NOT_FOUND, msg)
+    } else if msg.contains("must be") {
+        (StatusCode::BAD_REQUEST, msg)
+    } else {
+        tracing::error!("{} error: {:?}", context, e);
+        (StatusCode::INTERNAL_SERVER_ERROR, msg)
+    }
+}
+
+pub async fn rollup(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+    Path(scenario_id): Path<Uuid>,
+    Query(query): Query<PlRollupQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let service = report_service(&state);
+
+    match service.rollup(scenario_id, query.period).await {
+        Ok(rollup) => Ok((StatusCode::OK, Json(rollup))),
+        Err(e) => Err(report_error("P/L rollup", e)),
+    }
+}
+
+pub async fn pl_statement(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+    Path(scenario_id): Path<Uuid>,
+    Query(query): Query<PlStatementQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let service = report_service(&state);
+
+    match service
+        .pl_statement(
+            scenario_id,
+            query.entry_category,
+            query.node_id,
+            query.service_id,
+            query.period,
+        )
+        .await
+    {
+        Ok(statement) => Ok((StatusCode::OK, Json(statement))),
+        Err(e) => Err(report_error("P/L statement", e)),
+    }
+}
+
+pub async fn variance(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+    Path(scenario_id): Path<Uuid>,
+    Query(query): Query<PlVarianceQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let service = report_service(&state);
+
+    match service
+        .variance(
+            scenario_id,
+            query.from,
+            query.to,
+            query.service_id,
+            query.node_id,
+            query.period,
+        )
+        .await
+    {
+        Ok(variance) => Ok((StatusCode::OK, Json(variance))),
+        Err(e) => Err(report_error("P/L variance", e)),
+    }
+}
+
+pub async fn compare(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+    Query(query): Query<CompareScenariosQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let service = report_service(&state);
+
+    match service.compare(query.base_id, query.target_id).await {
+        Ok(comparison) => Ok((StatusCode::OK, Json(comparison))),
+        Err(e) => Err(report_error("Scenario comparison", e)),
+    }
+}
+
+pub async fn compare_with_parent(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+    Path(scenario_id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let service = report_service(&state);
+
+    match service.compare_with_parent(scenario_id).await {
+        Ok(comparison) => Ok((StatusCode::OK, Json(comparison))),
+        Err(e) => Err(report_error("Parent scenario comparison", e)),
+    }
+}
+
+pub async fn service_pl(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+    Path(scenario_id): Path<Uuid>,
+    Query(query): Query<ServicePlQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let service = report_service(&state);
+
+    match service
+        .service_pl(scenario_id, query.service_id, query.period)
+        .await
+    {
+        Ok(report) => Ok((StatusCode::OK, Json(report))),
+        Err(e) => Err(report_error("Service P/L", e)),
+    }
+}
+
+pub async fn forecast(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+    Path(scenario_id): Path<Uuid>,
+    Query(query): Query<PlForecastQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let service = report_service(&state);
+
+    match service.forecast(scenario_id, query.cutoff).await {
+        Ok(forecast) => Ok((StatusCode::OK, Json(forecast))),
+        Err(e) => Err(report_error("P/L forecast", e)),
+    }
+}
+
+pub async fn grid(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+    Path(scenario_id): Path<Uuid>,
+    Query(query): Query<PlGridQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let service = report_service(&state);
+
+    match service.grid(scenario_id, query.entry_category).await {
+        Ok(grid) => Ok((StatusCode::OK, Json(grid))),
+        Err(e) => Err(report_error("P/L grid", e)),
+    }
+}
+
+pub async fn export_xlsx(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+    Path(scenario_id): Path<Uuid>,
+    Query(query): Query<ExportScenarioQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let service = report_service(&state);
+
+    let export = service
+        .export_data(scenario_id, query.period)
+        .await
+        .map_err(|e| report_error("Scenario export", e))?;
+
+    let bytes = xlsx::render_scenario_workbook(&export).map_err(|e| {
+        tracing::error!("Failed to render xlsx: {:?}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    })?;
+
+    let headers = [
+        (
+            header::CONTENT_TYPE,
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet".to_string(),
+        ),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"scenario-{}.xlsx\"", scenario_id),
+        ),
+    ];
+
+    Ok((StatusCode::OK, headers, bytes))
+}
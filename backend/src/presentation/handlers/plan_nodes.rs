@@ -8,13 +8,19 @@ use axum::{
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::infrastructure::persistence::pl_entries::PlEntryRepositoryImpl;
 use crate::infrastructure::persistence::scenarios::ScenarioRepositoryImpl;
-use crate::presentation::dtos::UpdatePlanNodeRequest;
+use crate::presentation::dtos::{
+    CopyPlanNodeRequest, MovePlanNodeRequest, UpdatePlanNodeAssigneesRequest, UpdatePlanNodeRequest,
+};
 use crate::{
     application::services::plan_nodes::PlanNodeService,
     infrastructure::persistence::plan_nodes::PlanNodeRepositoryImpl,
     presentation::{
-        dtos::{CreatePlanNodeRequest, ListPlanNodesQuery},
+        dtos::{
+            CreatePlanNodeRequest, DeletePlanNodeQuery, ListPlanNodesQuery, PlanNodeTrashQuery,
+            PlanNodeTreeQuery,
+        },
         extractors::AuthUser,
     },
     state::AppState,
@@ -31,7 +37,13 @@ pub async fn create(
 
     let plan_node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
     let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
-    let service = PlanNodeService::new(plan_node_repo, scenario_repo);
+    let entry_repo = PlEntryRepositoryImpl::new(state.pool.clone());
+    let service = PlanNodeService::new(
+        state.pool.clone(),
+        plan_node_repo,
+        scenario_repo,
+        entry_repo,
+    );
 
     match service
         .create(
@@ -43,6 +55,7 @@ pub async fn create(
             payload.display_order,
             payload.service_id,
             auth_user.id,
+            &auth_user.role,
         )
         .await
     {
@@ -56,6 +69,8 @@ pub async fn create(
             {
                 tracing::warn!("PlanNode validation failed: {}", msg);
                 Err((StatusCode::BAD_REQUEST, msg))
+            } else if msg.contains("Read-Only") {
+                Err((StatusCode::FORBIDDEN, msg))
             } else {
                 tracing::error!("Failed to create plan node: {}", e);
                 Err((StatusCode::INTERNAL_SERVER_ERROR, msg))
@@ -71,7 +86,13 @@ pub async fn list(
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
     let plan_node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
     let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
-    let service = PlanNodeService::new(plan_node_repo, scenario_repo);
+    let entry_repo = PlEntryRepositoryImpl::new(state.pool.clone());
+    let service = PlanNodeService::new(
+        state.pool.clone(),
+        plan_node_repo,
+        scenario_repo,
+        entry_repo,
+    );
 
     let result = match query.scenario_id {
         Some(id) => service.list_by_scenario(id).await,
@@ -84,6 +105,38 @@ pub async fn list(
     }
 }
 
+pub async fn tree(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+    Query(query): Query<PlanNodeTreeQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let plan_node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
+    let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
+    let entry_repo = PlEntryRepositoryImpl::new(state.pool.clone());
+    let service = PlanNodeService::new(
+        state.pool.clone(),
+        plan_node_repo,
+        scenario_repo,
+        entry_repo,
+    );
+
+    match service
+        .tree(query.scenario_id, query.root_id, query.max_depth)
+        .await
+    {
+        Ok(tree) => Ok(Json(tree)),
+        Err(e) => {
+            let msg = e.to_string();
+            if msg.contains("not found") {
+                Err((StatusCode::NOT_FOUND, msg))
+            } else {
+                tracing::error!("Failed to build plan tree: {:?}", e);
+                Err((StatusCode::INTERNAL_SERVER_ERROR, msg))
+            }
+        }
+    }
+}
+
 pub async fn update(
     State(state): State<AppState>,
     auth_user: AuthUser,
@@ -92,9 +145,15 @@ pub async fn update(
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
     let plan_node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
     let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
-    let service = PlanNodeService::new(plan_node_repo, scenario_repo);
+    let entry_repo = PlEntryRepositoryImpl::new(state.pool.clone());
+    let service = PlanNodeService::new(
+        state.pool.clone(),
+        plan_node_repo,
+        scenario_repo,
+        entry_repo,
+    );
 
-    match service.update(id, req, auth_user.id).await {
+    match service.update(id, req, auth_user.id, &auth_user.role).await {
         Ok(node) => Ok(Json(node)),
         Err(e) => {
             let err_msg = e.to_string();
@@ -110,16 +169,120 @@ pub async fn update(
     }
 }
 
+pub async fn move_node(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<MovePlanNodeRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let plan_node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
+    let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
+    let entry_repo = PlEntryRepositoryImpl::new(state.pool.clone());
+    let service = PlanNodeService::new(
+        state.pool.clone(),
+        plan_node_repo,
+        scenario_repo,
+        entry_repo,
+    );
+
+    match service
+        .move_node(
+            id,
+            req.parent_id,
+            req.display_order,
+            auth_user.id,
+            &auth_user.role,
+        )
+        .await
+    {
+        Ok(node) => Ok(Json(node)),
+        Err(e) => {
+            let err_msg = e.to_string();
+            if err_msg.contains("Parent node not found")
+                || err_msg.contains("cannot be a child of")
+                || err_msg.contains("Cannot move")
+                || err_msg.contains("Only 'Initiative'")
+            {
+                Err((StatusCode::BAD_REQUEST, err_msg))
+            } else if err_msg.contains("not found") {
+                Err((StatusCode::NOT_FOUND, err_msg))
+            } else if err_msg.contains("Read-Only") {
+                Err((StatusCode::FORBIDDEN, err_msg))
+            } else {
+                tracing::error!("Plan Node Move Error: {:?}", e);
+                Err((StatusCode::INTERNAL_SERVER_ERROR, err_msg))
+            }
+        }
+    }
+}
+
+pub async fn copy(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<CopyPlanNodeRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let plan_node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
+    let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
+    let entry_repo = PlEntryRepositoryImpl::new(state.pool.clone());
+    let service = PlanNodeService::new(
+        state.pool.clone(),
+        plan_node_repo,
+        scenario_repo,
+        entry_repo,
+    );
+
+    match service
+        .copy_subtree(
+            id,
+            req.parent_id,
+            req.options(),
+            auth_user.id,
+            &auth_user.role,
+        )
+        .await
+    {
+        Ok(summary) => Ok((StatusCode::CREATED, Json(summary))),
+        Err(e) => {
+            let err_msg = e.to_string();
+            if err_msg.contains("Parent node not found")
+                || err_msg.contains("cannot be a child of")
+                || err_msg.contains("Only 'Initiative'")
+                || err_msg.contains("Title cannot be empty")
+            {
+                Err((StatusCode::BAD_REQUEST, err_msg))
+            } else if err_msg.contains("not found") {
+                Err((StatusCode::NOT_FOUND, err_msg))
+            } else if err_msg.contains("Read-Only") {
+                Err((StatusCode::FORBIDDEN, err_msg))
+            } else {
+                tracing::error!("Plan Node Copy Error: {:?}", e);
+                Err((StatusCode::INTERNAL_SERVER_ERROR, err_msg))
+            }
+        }
+    }
+}
+
 pub async fn delete(
     State(state): State<AppState>,
-    _auth_user: AuthUser,
+    auth_user: AuthUser,
     Path(id): Path<Uuid>,
+    Query(query): Query<DeletePlanNodeQuery>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
     let plan_node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
     let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
-    let service = PlanNodeService::new(plan_node_repo, scenario_repo);
+    let entry_repo = PlEntryRepositoryImpl::new(state.pool.clone());
+    let service = PlanNodeService::new(
+        state.pool.clone(),
+        plan_node_repo,
+        scenario_repo,
+        entry_repo,
+    );
 
-    match service.delete(id).await {
+    match service
+        .delete(id, query.cascade, auth_user.id, &auth_user.role)
+        .await
+    {
         Ok(_) => Ok(StatusCode::NO_CONTENT),
         Err(e) => {
             let err_msg = e.to_string();
@@ -136,3 +299,104 @@ pub async fn delete(
         }
     }
 }
+
+pub async fn restore(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let plan_node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
+    let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
+    let entry_repo = PlEntryRepositoryImpl::new(state.pool.clone());
+    let service = PlanNodeService::new(
+        state.pool.clone(),
+        plan_node_repo,
+        scenario_repo,
+        entry_repo,
+    );
+
+    match service.restore(id, auth_user.id, &auth_user.role).await {
+        Ok(node) => Ok(Json(node)),
+        Err(e) => {
+            let err_msg = e.to_string();
+            if err_msg.contains("Parent node is deleted") {
+                Err((StatusCode::BAD_REQUEST, err_msg))
+            } else if err_msg.contains("not found") {
+                Err((StatusCode::NOT_FOUND, err_msg))
+            } else if err_msg.contains("Read-Only") {
+                Err((StatusCode::FORBIDDEN, err_msg))
+            } else if err_msg.contains("changed by another user")
+                || err_msg.contains("already exists")
+            {
+                Err((StatusCode::CONFLICT, err_msg))
+            } else {
+                tracing::error!("Plan Node Restore Error: {:?}", e);
+                Err((StatusCode::INTERNAL_SERVER_ERROR, err_msg))
+            }
+        }
+    }
+}
+
+pub async fn trash(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+    Query(query): Query<PlanNodeTrashQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let plan_node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
+    let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
+    let entry_repo = PlEntryRepositoryImpl::new(state.pool.clone());
+    let service = PlanNodeService::new(
+        state.pool.clone(),
+        plan_node_repo,
+        scenario_repo,
+        entry_repo,
+    );
+
+    match service.list_trash(query.scenario_id).await {
+        Ok(nodes) => Ok(Json(nodes)),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+pub async fn update_assignees(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdatePlanNodeAssigneesRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let plan_node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
+    let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
+    let entry_repo = PlEntryRepositoryImpl::new(state.pool.clone());
+    let service = PlanNodeService::new(
+        state.pool.clone(),
+        plan_node_repo,
+        scenario_repo,
+        entry_repo,
+    );
+
+    match service
+        .update_assignees(
+            id,
+            req.owner_id,
+            req.editor_ids,
+            auth_user.id,
+            &auth_user.role,
+        )
+        .await
+    {
+        Ok(node) => Ok(Json(node)),
+        Err(e) => {
+            let err_msg = e.to_string();
+            if err_msg.contains("Assigned user not found") {
+                Err((StatusCode::BAD_REQUEST, err_msg))
+            } else if err_msg.contains("not found") {
+                Err((StatusCode::NOT_FOUND, err_msg))
+            } else if err_msg.contains("Permission denied") || err_msg.contains("Read-Only") {
+                Err((StatusCode::FORBIDDEN, err_msg))
+            } else {
+                tracing::error!("Plan Node Assignees Error: {:?}", e);
+                Err((StatusCode::INTERNAL_SERVER_ERROR, err_msg))
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use axum::extract::Path;
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use uuid::Uuid;
+
+use crate::{
+    application::services::{pl_entries::PlEntryService, scenario_merges::ScenarioMergeService},
+    domain::closed_months::MonthClosedError,
+    infrastructure::persistence::{
+        account_item::AccountItemRepositoryImpl, closed_months::ClosedMonthRepositoryImpl,
+        history::PlEntryHistoryRepositoryImpl, ledger_mappings::LedgerMappingRepositoryImpl,
+        pl_entries::PlEntryRepositoryImpl, plan_nodes::PlanNodeRepositoryImpl,
+        scenarios::ScenarioRepositoryImpl,
+    },
+    presentation::{dtos::MergeBranchRequest, extractors::AuthUser},
+    state::AppState,
+};
+
+type MergeService = ScenarioMergeService<
+    PlEntryRepositoryImpl,
+    PlanNodeRepositoryImpl,
+    PlEntryHistoryRepositoryImpl,
+    ScenarioRepositoryImpl,
+    AccountItemRepositoryImpl,
+    LedgerMappingRepositoryImpl,
+    ClosedMonthRepositoryImpl,
+>;
+
+fn merge_service(state: &AppState) -> MergeService {
+    let entry_repo = PlEntryRepositoryImpl::new(state.pool.clone());
+    let node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
+    let history_repo = PlEntryHistoryRepositoryImpl::new(state.pool.clone());
+    let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
+    let account_item_repo = AccountItemRepositoryImpl::new(state.pool.clone());
+    let ledger_mapping_repo = LedgerMappingRepositoryImpl::new(state.pool.clone());
+    let closed_month_repo = ClosedMonthRepositoryImpl::new(state.pool.clone());
+
+    let entry_service = PlEntryService::new(
+        state.pool.clone(),
+        entry_repo.clone(),
+        node_repo.clone(),
+        history_repo.clone(),
+        scenario_repo.clone(),
+        account_item_repo,
+        ledger_mapping_repo,
+        closed_month_repo,
+    );
+
+    ScenarioMergeService::new(
+        state.pool.clone(),
+        entry_repo,
+        node_repo,
+        history_repo,
+        scenario_repo,
+        entry_service,
+    )
+}
+
+fn merge_error(e: anyhow::Error) -> (StatusCode, String) {
+    let msg = e.to_string();
+    if e.is::<MonthClosedError>() {
+        (StatusCode::CONFLICT, msg)
+    } else if msg.contains("not found") {
+        (StatusCode::NOT_FOUND, msg)
+    } else if msg.contains("Read-Only") {
+        (StatusCode::FORBIDDEN, msg)
+    } else if msg.contains("Merge conflict") {
+        (StatusCode::CONFLICT, msg)
+    } else if msg.contains("must be")
+        || msg.contains("requires merging")
+        || msg.contains("cannot be a child of")
+        || msg.contains("can be a root")
+        || msg.contains("outside the scenario period")
+        || msg.contains("Unknown")
+        || msg.contains("削除できません")
+    {
+        (StatusCode::BAD_REQUEST, msg)
+    } else {
+        tracing::error!("Scenario merge error: {:?}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, msg)
+    }
+}
+
+pub async fn merge_preview(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+    Path(branch_id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let service = merge_service(&state);
+
+    match service.merge_preview(branch_id).await {
+        Ok(changes) => Ok((StatusCode::OK, Json(changes))),
+        Err(e) => Err(merge_error(e)),
+    }
+}
+
+pub async fn merge(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(branch_id): Path<Uuid>,
+    Json(payload): Json<MergeBranchRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let service = merge_service(&state);
+
+    match service
+        .merge_branch(
+            branch_id,
+            payload.keys,
+            payload.overwrite_conflicts,
+            auth_user.id,
+            &auth_user.role,
+        )
+        .await
+    {
+        Ok(report) => Ok((StatusCode::OK, Json(report))),
+        Err(e) => Err(merge_error(e)),
+    }
+}
@@ -0,0 +1,108 @@
+use axum::extract::Path;
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    application::services::scenario_snapshots::ScenarioSnapshotService,
+    domain::{scenario_snapshots::ScenarioSnapshot, user::UserRole},
+    infrastructure::persistence::{
+        account_item::AccountItemRepositoryImpl, history::PlEntryHistoryRepositoryImpl,
+        pl_entries::PlEntryRepositoryImpl, plan_nodes::PlanNodeRepositoryImpl,
+        scenarios::ScenarioRepositoryImpl, services::ServiceRepositoryImpl,
+        user::UserRepositoryImpl,
+    },
+    presentation::{dtos::ImportScenarioSnapshotQuery, extractors::AuthUser},
+    state::AppState,
+};
+
+type SnapshotService = ScenarioSnapshotService<
+    ScenarioRepositoryImpl,
+    PlanNodeRepositoryImpl,
+    PlEntryRepositoryImpl,
+    PlEntryHistoryRepositoryImpl,
+    AccountItemRepositoryImpl,
+    ServiceRepositoryImpl,
+    UserRepositoryImpl,
+>;
+
+fn snapshot_service(state: &AppState) -> SnapshotService {
+    let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
+    let node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
+    let entry_repo = PlEntryRepositoryImpl::new(state.pool.clone());
+    let history_repo = PlEntryHistoryRepositoryImpl::new(state.pool.clone());
+    let account_item_repo = AccountItemRepositoryImpl::new(state.pool.clone());
+    let service_repo = ServiceRepositoryImpl::new(state.pool.clone());
+    let user_repo = UserRepositoryImpl::new(state.pool.clone());
+
+    ScenarioSnapshotService::new(
+        state.pool.clone(),
+        scenario_repo,
+        node_repo,
+        entry_repo,
+        history_repo,
+        account_item_repo,
+        service_repo,
+        user_repo,
+    )
+}
+
+pub async fn export(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+    Path(scenario_id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let service = snapshot_service(&state);
+
+    match service.export(scenario_id).await {
+        Ok(snapshot) => {
+            let headers = [(
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"scenario-{}.json\"", scenario_id),
+            )];
+            Ok((StatusCode::OK, headers, Json(snapshot)))
+        }
+        Err(e) => {
+            let msg = e.to_string();
+            if msg.contains("Scenario not found") {
+                Err((StatusCode::NOT_FOUND, msg))
+            } else {
+                tracing::error!("Snapshot export error: {}", e);
+                Err((StatusCode::INTERNAL_SERVER_ERROR, msg))
+            }
+        }
+    }
+}
+
+pub async fn import(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<ImportScenarioSnapshotQuery>,
+    Json(snapshot): Json<ScenarioSnapshot>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    if auth_user.role != UserRole::Admin {
+        return Err((StatusCode::FORBIDDEN, "Permission denied".to_string()));
+    }
+
+    let service = snapshot_service(&state);
+
+    match service.import(snapshot, query.name, auth_user.id).await {
+        Ok(scenario) => Ok((StatusCode::CREATED, Json(scenario))),
+        Err(e) => {
+            let msg = e.to_string();
+            if msg.contains("snapshot")
+                || msg.contains("cannot be empty")
+                || msg.contains("must be before")
+            {
+                Err((StatusCode::BAD_REQUEST, msg))
+            } else {
+                tracing::error!("Snapshot import error: {}", e);
+                Err((StatusCode::INTERNAL_SERVER_ERROR, msg))
+            }
+        }
+    }
+}
@@ -1,19 +1,56 @@
-use axum::extract::Path;
-use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use axum::extract::{Path, Query};
+use axum::{
+    Json,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::infrastructure::persistence::account_item::AccountItemRepositoryImpl;
 use crate::infrastructure::persistence::pl_entries::PlEntryRepositoryImpl;
 use crate::infrastructure::persistence::plan_nodes::PlanNodeRepositoryImpl;
-use crate::presentation::dtos::RolloverScenarioRequest;
+use crate::infrastructure::persistence::services::ServiceRepositoryImpl;
+use crate::presentation::dtos::{
+    BranchScenarioRequest, RolloverScenarioQuery, RolloverScenarioRequest, ScenarioStatusRequest,
+};
 use crate::{
     application::services::scenarios::ScenarioService,
-    domain::user::UserRole,
+    domain::{
+        scenarios::{ScenarioKind, ScenarioStatus},
+        user::UserRole,
+    },
     infrastructure::persistence::scenarios::ScenarioRepositoryImpl,
     presentation::{dtos::CreateScenarioRequest, extractors::AuthUser},
     state::AppState,
 };
 
+type ScenarioServiceImpl = ScenarioService<
+    ScenarioRepositoryImpl,
+    PlanNodeRepositoryImpl,
+    PlEntryRepositoryImpl,
+    ServiceRepositoryImpl,
+    AccountItemRepositoryImpl,
+>;
+
+fn scenario_service(state: &AppState) -> ScenarioServiceImpl {
+    let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
+    let node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
+    let entry_repo = PlEntryRepositoryImpl::new(state.pool.clone());
+    let service_repo = ServiceRepositoryImpl::new(state.pool.clone());
+    let account_item_repo = AccountItemRepositoryImpl::new(state.pool.clone());
+
+    ScenarioService::new(
+        state.pool.clone(),
+        scenario_repo,
+        node_repo,
+        entry_repo,
+        service_repo,
+        account_item_repo,
+    )
+}
+
 pub async fn create(
     State(state): State<AppState>,
     auth_user: AuthUser,
@@ -27,11 +64,7 @@ pub async fn create(
         return Err((StatusCode::BAD_REQUEST, format!("Validation error: {}", e)));
     }
 
-    let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
-    let node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
-    let entry_repo = PlEntryRepositoryImpl::new(state.pool.clone());
-
-    let service = ScenarioService::new(scenario_repo, node_repo, entry_repo);
+    let service = scenario_service(&state);
 
     match service
         .create(
@@ -39,6 +72,7 @@ pub async fn create(
             payload.description,
             payload.start_date,
             payload.end_date,
+            payload.kind,
             auth_user.id,
         )
         .await
@@ -55,11 +89,7 @@ pub async fn list(
     State(state): State<AppState>,
     _auth_user: AuthUser,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
-    let node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
-    let entry_repo = PlEntryRepositoryImpl::new(state.pool.clone());
-
-    let service = ScenarioService::new(scenario_repo, node_repo, entry_repo);
+    let service = scenario_service(&state);
 
     match service.list_all().await {
         Ok(res) => Ok((StatusCode::OK, Json(res))),
@@ -71,17 +101,72 @@ pub async fn activate(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
-    let node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
-    let entry_repo = PlEntryRepositoryImpl::new(state.pool.clone());
-
-    let service = ScenarioService::new(scenario_repo, node_repo, entry_repo);
+    let service = scenario_service(&state);
 
     match service.activate(id).await {
         Ok(_) => Ok(StatusCode::NO_CONTENT),
         Err(e) => {
-            tracing::error!("Error activating scenario: {:?}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            let msg = e.to_string();
+            if msg.contains("not found") {
+                Err((StatusCode::NOT_FOUND, msg))
+            } else if msg.contains("cannot be activated") {
+                Err((StatusCode::CONFLICT, msg))
+            } else {
+                tracing::error!("Error activating scenario: {:?}", e);
+                Err((StatusCode::INTERNAL_SERVER_ERROR, msg))
+            }
+        }
+    }
+}
+
+pub async fn lock(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    if auth_user.role != UserRole::Admin {
+        return Err((StatusCode::FORBIDDEN, "Permission denied".to_string()));
+    }
+
+    let service = scenario_service(&state);
+
+    match service.lock(id, auth_user.id).await {
+        Ok(scenario) => Ok((StatusCode::OK, Json(scenario))),
+        Err(e) => {
+            let msg = e.to_string();
+            if msg.contains("not found") {
+                Err((StatusCode::NOT_FOUND, msg))
+            } else {
+                tracing::error!("Error locking scenario: {:?}", e);
+                Err((StatusCode::INTERNAL_SERVER_ERROR, msg))
+            }
+        }
+    }
+}
+
+pub async fn unlock(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    if auth_user.role != UserRole::Admin {
+        return Err((StatusCode::FORBIDDEN, "Permission denied".to_string()));
+    }
+
+    let service = scenario_service(&state);
+
+    match service.unlock(id, auth_user.id, &auth_user.role).await {
+        Ok(scenario) => Ok((StatusCode::OK, Json(scenario))),
+        Err(e) => {
+            let msg = e.to_string();
+            if msg.contains("not found") {
+                Err((StatusCode::NOT_FOUND, msg))
+            } else if msg.contains("changed by another user") {
+                Err((StatusCode::CONFLICT, msg))
+            } else {
+                tracing::error!("Error unlocking scenario: {:?}", e);
+                Err((StatusCode::INTERNAL_SERVER_ERROR, msg))
+            }
         }
     }
 }
@@ -90,33 +175,157 @@ pub async fn rollover(
     State(state): State<AppState>,
     auth_user: AuthUser,
     Path(source_id): Path<Uuid>,
+    Query(query): Query<RolloverScenarioQuery>,
     Json(payload): Json<RolloverScenarioRequest>,
+) -> Result<Response, (StatusCode, String)> {
+    let service = scenario_service(&state);
+
+    let options = payload.options();
+
+    let result = if query.dry_run {
+        service
+            .preview_rollover(
+                source_id,
+                payload.name,
+                payload.start_date,
+                payload.end_date,
+                options,
+                auth_user.id,
+            )
+            .await
+            .map(|summary| (StatusCode::OK, Json(summary)).into_response())
+    } else {
+        service
+            .rollover(
+                source_id,
+                payload.name,
+                payload.start_date,
+                payload.end_date,
+                options,
+                auth_user.id,
+            )
+            .await
+            .map(|new_scenario| (StatusCode::CREATED, Json(new_scenario)).into_response())
+    };
+
+    match result {
+        Ok(response) => Ok(response),
+        Err(e) => {
+            tracing::error!("Rollover error: {:?}", e);
+            let msg = e.to_string();
+            if msg.contains("not found") {
+                Err((StatusCode::NOT_FOUND, msg))
+            } else if msg.contains("Start date")
+                || msg.contains("do not overlap")
+                || msg.contains("Uplift")
+                || msg.contains("cannot be rolled over")
+            {
+                Err((StatusCode::BAD_REQUEST, msg))
+            } else {
+                Err((StatusCode::INTERNAL_SERVER_ERROR, msg))
+            }
+        }
+    }
+}
+
+pub async fn branch(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(source_id): Path<Uuid>,
+    Json(payload): Json<BranchScenarioRequest>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let scenario_repo = ScenarioRepositoryImpl::new(state.pool.clone());
-    let node_repo = PlanNodeRepositoryImpl::new(state.pool.clone());
-    let entry_repo = PlEntryRepositoryImpl::new(state.pool.clone());
+    if let Err(e) = payload.validate() {
+        return Err((StatusCode::BAD_REQUEST, format!("Validation error: {}", e)));
+    }
 
-    let service = ScenarioService::new(scenario_repo, node_repo, entry_repo);
+    let service = scenario_service(&state);
 
     match service
-        .rollover(
+        .branch(
             source_id,
             payload.name,
-            payload.start_date,
-            payload.end_date,
+            payload.description,
+            payload.kind.unwrap_or(ScenarioKind::WhatIf),
             auth_user.id,
+            &auth_user.role,
         )
         .await
     {
-        Ok(new_scenario) => Ok((StatusCode::CREATED, Json(new_scenario))),
+        Ok(branch) => Ok((StatusCode::CREATED, Json(branch))),
         Err(e) => {
-            tracing::error!("Rollover error: {:?}", e);
             let msg = e.to_string();
             if msg.contains("not found") {
                 Err((StatusCode::NOT_FOUND, msg))
+            } else if msg.contains("Permission denied") {
+                Err((StatusCode::FORBIDDEN, msg))
+            } else if msg.contains("cannot be empty") {
+                Err((StatusCode::BAD_REQUEST, msg))
             } else {
+                tracing::error!("Branch scenario error: {:?}", e);
                 Err((StatusCode::INTERNAL_SERVER_ERROR, msg))
             }
         }
     }
 }
+
+// 承認フローの状態変更 (権限チェックはドメインで行う)
+async fn change_status(
+    state: AppState,
+    auth_user: AuthUser,
+    id: Uuid,
+    to: ScenarioStatus,
+    payload: Option<Json<ScenarioStatusRequest>>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let service = scenario_service(&state);
+
+    let Json(payload) = payload.unwrap_or_default();
+
+    match service
+        .transition_status(id, to, payload.comment, auth_user.id, &auth_user.role)
+        .await
+    {
+        Ok(scenario) => Ok((StatusCode::OK, Json(scenario))),
+        Err(e) => {
+            let msg = e.to_string();
+            if msg.contains("not found") {
+                Err((StatusCode::NOT_FOUND, msg))
+            } else if msg.contains("Permission denied") {
+                Err((StatusCode::FORBIDDEN, msg))
+            } else if msg.contains("Cannot change") || msg.contains("changed by another user") {
+                Err((StatusCode::CONFLICT, msg))
+            } else if msg.contains("is required") {
+                Err((StatusCode::BAD_REQUEST, msg))
+            } else {
+                tracing::error!("Scenario status error: {:?}", e);
+                Err((StatusCode::INTERNAL_SERVER_ERROR, msg))
+            }
+        }
+    }
+}
+
+pub async fn submit(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+    payload: Option<Json<ScenarioStatusRequest>>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    change_status(state, auth_user, id, ScenarioStatus::Submitted, payload).await
+}
+
+pub async fn approve(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+    payload: Option<Json<ScenarioStatusRequest>>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    change_status(state, auth_user, id, ScenarioStatus::Approved, payload).await
+}
+
+pub async fn reject(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+    payload: Option<Json<ScenarioStatusRequest>>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    change_status(state, auth_user, id, ScenarioStatus::Rejected, payload).await
+}